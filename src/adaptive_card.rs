@@ -0,0 +1,132 @@
+use crate::protocol::{AdaptiveCard, CardElement};
+
+/// Rebuild the whole text of the message from its adaptive cards.
+/// Returns `None` if cards don't contain any text.
+pub(crate) fn cards_text(adaptive_cards: &[AdaptiveCard]) -> Option<String> {
+    let mut blocks = Vec::new();
+    for card in adaptive_cards {
+        elements_text(&card.body, &mut blocks);
+    }
+
+    if blocks.is_empty() {
+        return None;
+    }
+
+    Some(blocks.join("\n\n"))
+}
+
+fn elements_text(elements: &[CardElement], blocks: &mut Vec<String>) {
+    for element in elements {
+        match element {
+            CardElement::TextBlock { text: Some(text) } => {
+                if !text.is_empty() {
+                    blocks.push(text.clone());
+                }
+            }
+            CardElement::RichTextBlock { inlines } => {
+                let text: String = inlines
+                    .iter()
+                    .filter_map(|inline| inline.text.as_deref())
+                    .collect();
+
+                if !text.is_empty() {
+                    blocks.push(text);
+                }
+            }
+            CardElement::FactSet { facts } => {
+                let facts: Vec<String> = facts
+                    .iter()
+                    .map(|fact| {
+                        let title = fact.title.as_deref().unwrap_or_default();
+                        let value = fact.value.as_deref().unwrap_or_default();
+                        format!("{title}: {value}")
+                    })
+                    .collect();
+
+                if !facts.is_empty() {
+                    blocks.push(facts.join("\n"));
+                }
+            }
+            CardElement::Image {
+                url: Some(url),
+                alt_text,
+            } => {
+                let alt = alt_text.as_deref().unwrap_or_default();
+                blocks.push(format!("![{alt}]({url})"));
+            }
+            CardElement::Container { items } | CardElement::Column { items } => {
+                elements_text(items, blocks);
+            }
+            CardElement::ColumnSet { columns } => elements_text(columns, blocks),
+            CardElement::TextBlock { text: None }
+            | CardElement::Image { url: None, .. }
+            | CardElement::Unknown => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn cards(value: serde_json::Value) -> Vec<AdaptiveCard> {
+        serde_json::from_value(value).unwrap()
+    }
+
+    #[test]
+    fn text_of_nested_elements() {
+        let cards = cards(json!([{ "body": [
+            { "type": "TextBlock", "text": "Paris is the capital[^1^]." },
+            { "type": "Container", "items": [
+                { "type": "ColumnSet", "columns": [
+                    { "type": "Column", "items": [
+                        { "type": "FactSet", "facts": [
+                            { "title": "Population", "value": "2.1M" },
+                            { "title": "Area" }
+                        ]}
+                    ]},
+                    { "type": "Column", "items": [
+                        { "type": "Image", "url": "https://example.com/paris.jpg", "altText": "Paris" }
+                    ]}
+                ]}
+            ]},
+            { "type": "RichTextBlock", "inlines": [
+                { "type": "TextRun", "text": "Learn " },
+                { "type": "TextRun" },
+                { "type": "TextRun", "text": "more" }
+            ]},
+            { "type": "ActionSet" }
+        ]}]));
+
+        assert_eq!(
+            cards_text(&cards).unwrap(),
+            "Paris is the capital[^1^].\n\n\
+            Population: 2.1M\nArea: \n\n\
+            ![Paris](https://example.com/paris.jpg)\n\n\
+            Learn more"
+        );
+    }
+
+    #[test]
+    fn blocks_of_all_cards() {
+        let cards = cards(json!([
+            { "body": [{ "type": "TextBlock", "text": "First" }] },
+            { "body": [{ "type": "TextBlock", "text": "Second" }] }
+        ]));
+        assert_eq!(cards_text(&cards).unwrap(), "First\n\nSecond");
+    }
+
+    #[test]
+    fn no_text() {
+        let cards = cards(json!([{ "body": [
+            { "type": "TextBlock", "text": "" },
+            { "type": "RichTextBlock", "inlines": [] },
+            { "type": "FactSet", "facts": [] },
+            { "type": "Image" },
+            { "type": "Container", "items": [] }
+        ]}]));
+        assert_eq!(cards_text(&cards), None);
+        assert_eq!(cards_text(&[]), None);
+    }
+}
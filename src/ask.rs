@@ -0,0 +1,195 @@
+use bing_ai_rust::{
+    render_footnotes, BingAIWs, Citation, ConversationStore, ErrorKind, Event, ImageInput,
+    SydneyError, SydneyResponse,
+};
+use clap::ValueEnum;
+use futures_util::StreamExt;
+use std::io::Write;
+use std::path::PathBuf;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum OutputFormat {
+    /// Answer streamed as plain text
+    Text,
+    /// Answer with citations as markdown footnotes, printed when complete
+    Markdown,
+    /// Every response event (or error) as a versioned json line
+    Json,
+}
+
+pub struct AskOptions {
+    pub citations: bool,
+    pub suggestions: bool,
+    pub format: OutputFormat,
+    pub image: Option<ImageInput>,
+    pub images_dir: Option<PathBuf>,
+}
+
+/// Ask single prompt, print the answer to stdout and save the conversation.
+pub async fn ask(mut ai: BingAIWs, prompt: &str, opts: AskOptions) -> Result<(), SydneyError> {
+    ai.set_close_ws_after(true);
+    ai.set_citations(opts.citations);
+    ai.set_suggestions(opts.suggestions);
+    ai.set_deltas(opts.format != OutputFormat::Json);
+
+    let mut stream = match opts.image.clone() {
+        Some(image) => ai.ask_with_image(prompt, image).await?,
+        None => ai.ask(prompt).await?,
+    };
+    let mut stdout = std::io::stdout().lock();
+    let mut answer = String::new();
+    let mut printed = 0;
+    let mut sources: Vec<Citation> = Vec::new();
+    let mut suggestions = Vec::new();
+    let mut image_prompts = Vec::new();
+
+    while let Some(msg) = stream.next().await {
+        let msg = msg?;
+
+        if opts.format == OutputFormat::Json {
+            let event = serde_json::to_string(&Event::response(msg))?;
+            writeln!(stdout, "{event}").map_err(anyhow::Error::from)?;
+            continue;
+        }
+
+        match msg {
+            SydneyResponse::StreamDelta(text) => answer.push_str(&text),
+            SydneyResponse::StreamRewrite { offset, text } => {
+                answer.truncate(offset);
+                answer.push_str(&text);
+            }
+            SydneyResponse::FinalText(text) => answer = text,
+            SydneyResponse::Sources(citations) => sources = citations,
+            SydneyResponse::SuggestedResponses(responses) => suggestions = responses,
+            SydneyResponse::ImageGenerationRequest { prompt } => image_prompts.push(prompt),
+            SydneyResponse::Rollover {
+                conversation_id, ..
+            } => eprintln!("Message limit reached, continuing in conversation {conversation_id}"),
+            SydneyResponse::StreamText(_) | SydneyResponse::Quota { .. } => {}
+        }
+
+        if opts.format == OutputFormat::Text
+            && answer.len() > printed
+            && answer.is_char_boundary(printed)
+        {
+            write!(stdout, "{}", &answer[printed..]).map_err(anyhow::Error::from)?;
+            stdout.flush().map_err(anyhow::Error::from)?;
+            printed = answer.len();
+        }
+    }
+
+    drop(stream);
+
+    if opts.format != OutputFormat::Json {
+        write_summary(&mut stdout, &opts, &answer, &sources, &suggestions)
+            .map_err(anyhow::Error::from)?;
+
+        for prompt in &image_prompts {
+            write_images(&mut stdout, &ai, &opts, prompt).await?;
+        }
+    }
+
+    ConversationStore::open_default()?.save(&ai.export_state())
+}
+
+fn write_summary(
+    out: &mut impl Write,
+    opts: &AskOptions,
+    answer: &str,
+    sources: &[Citation],
+    suggestions: &[String],
+) -> std::io::Result<()> {
+    match opts.format {
+        OutputFormat::Markdown => {
+            writeln!(out, "{}", render_footnotes(answer, sources).trim_end())?;
+
+            if !suggestions.is_empty() {
+                writeln!(out, "\n## Suggestions\n")?;
+                for suggestion in suggestions {
+                    writeln!(out, "- {suggestion}")?;
+                }
+            }
+        }
+        _ => {
+            writeln!(out)?;
+
+            if opts.citations && !sources.is_empty() {
+                writeln!(out)?;
+                for citation in sources {
+                    writeln!(
+                        out,
+                        "[{}] {} - {}",
+                        citation.index, citation.title, citation.url
+                    )?;
+                }
+            }
+
+            if !suggestions.is_empty() {
+                writeln!(out)?;
+                for (i, suggestion) in suggestions.iter().enumerate() {
+                    writeln!(out, "{}. {suggestion}", i + 1)?;
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Generate images requested in the answer and print their urls (or saved paths).
+async fn write_images(
+    out: &mut impl Write,
+    ai: &BingAIWs,
+    opts: &AskOptions,
+    prompt: &str,
+) -> Result<(), SydneyError> {
+    eprintln!("Generating images: {prompt}");
+    let creator = ai.image_creator();
+    let urls = creator.create(prompt).await?;
+
+    let lines: Vec<String> = match &opts.images_dir {
+        Some(dir) => creator
+            .download(&urls, dir)
+            .await?
+            .iter()
+            .map(|path| path.display().to_string())
+            .collect(),
+        None => urls,
+    };
+
+    writeln!(out).map_err(anyhow::Error::from)?;
+    for line in lines {
+        match opts.format {
+            OutputFormat::Markdown => writeln!(out, "![{prompt}]({line})"),
+            _ => writeln!(out, "{line}"),
+        }
+        .map_err(anyhow::Error::from)?;
+    }
+
+    Ok(())
+}
+
+/// Process exit code for the error, different for every variant.
+pub fn exit_code(e: &SydneyError) -> u8 {
+    match e.kind() {
+        ErrorKind::OtherError => 1,
+        ErrorKind::WebSocketNotConnected => 10,
+        ErrorKind::CreateConversationFailed => 11,
+        ErrorKind::HttpError => 12,
+        ErrorKind::WebSocketError => 13,
+        ErrorKind::JsonParsingError => 14,
+        ErrorKind::MaxMessagesCountLimitReached => 15,
+        ErrorKind::Throttled => 16,
+        ErrorKind::EndOfResponse => 17,
+        ErrorKind::IoError => 18,
+        ErrorKind::Disconnected => 19,
+        ErrorKind::ServerError => 20,
+        ErrorKind::ProtocolError => 21,
+        ErrorKind::ProxyError => 22,
+        ErrorKind::Timeout => 23,
+        ErrorKind::ImageUploadFailed => 24,
+        ErrorKind::ImageGenerationFailed => 25,
+        ErrorKind::CaptchaRequired => 26,
+        ErrorKind::MissingAuthCookie => 27,
+    }
+}
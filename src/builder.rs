@@ -0,0 +1,140 @@
+use crate::cookie_jar::CookieJar;
+use crate::cookies::BrowserCookies;
+use crate::image_creator::ImageCreator;
+use crate::proxy::Proxy;
+use crate::sydney::{build_client, BingAIWs, ClientConfig, SydneyError};
+use crate::timeout::Timeouts;
+use crate::types::{ConversationState, Tone};
+use std::sync::Arc;
+
+/// Configures endpoints and client identity before creating or resuming a conversation.
+///
+/// Defaults match the public Bing endpoints, so only values that differ need to be set:
+///
+/// ```no_run
+/// # async fn run() -> Result<(), bing_ai_rust::SydneyError> {
+/// use bing_ai_rust::{BingAIWsBuilder, Tone};
+///
+/// let ai = BingAIWsBuilder::new()
+///     .bundle_version("1.1600.0")
+///     .header("X-Forwarded-For", "1.1.1.1")
+///     .new_conversation(Tone::Creative)
+///     .await?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct BingAIWsBuilder {
+    config: ClientConfig,
+    cookies: Option<String>,
+    auth_missing: bool,
+}
+
+impl BingAIWsBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Url of the `turing/conversation/create` endpoint.
+    pub fn create_url(mut self, url: impl Into<String>) -> Self {
+        self.config.create_url = url.into();
+        self
+    }
+
+    /// Url of the `sydney/ChatHub` websocket.
+    pub fn ws_url(mut self, url: impl Into<String>) -> Self {
+        self.config.ws_url = url.into();
+        self
+    }
+
+    /// Url of the `images/kblob` endpoint used to upload images.
+    pub fn image_upload_url(mut self, url: impl Into<String>) -> Self {
+        self.config.image_upload_url = url.into();
+        self
+    }
+
+    /// Url of the Bing Image Creator (`images/create`).
+    pub fn image_creator_url(mut self, url: impl Into<String>) -> Self {
+        self.config.image_creator_url = url.into();
+        self
+    }
+
+    /// Bundle version sent with the create request.
+    pub fn bundle_version(mut self, version: impl Into<String>) -> Self {
+        self.config.bundle_version = version.into();
+        self
+    }
+
+    /// User agent of both the create request and the websocket handshake.
+    pub fn user_agent(mut self, user_agent: impl Into<String>) -> Self {
+        self.config.user_agent = user_agent.into();
+        self
+    }
+
+    /// Extra header sent with every request, including the websocket handshake.
+    pub fn header(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.config.headers.push((name.into(), value.into()));
+        self
+    }
+
+    /// Proxy for both the create request and the websocket. By default it's read
+    /// from the environment with [`Proxy::from_env`].
+    pub fn proxy(mut self, proxy: Proxy) -> Self {
+        self.config.proxy = Some(proxy);
+        self
+    }
+
+    /// Connect directly, ignoring proxy environment variables.
+    pub fn no_proxy(mut self) -> Self {
+        self.config.proxy = None;
+        self
+    }
+
+    /// Limits for the create request, websocket handshake and answer frames.
+    pub fn timeouts(mut self, timeouts: Timeouts) -> Self {
+        self.config.timeouts = timeouts;
+        self
+    }
+
+    /// Raw `Cookie:` header value used to authenticate.
+    pub fn cookies(mut self, cookies: impl Into<String>) -> Self {
+        self.cookies = Some(cookies.into());
+        self.auth_missing = false;
+        self
+    }
+
+    /// Cookies loaded from a browser export. Creating a conversation fails with
+    /// [`SydneyError::MissingAuthCookie`] when they don't contain the `_U` cookie.
+    pub fn browser_cookies(mut self, cookies: &BrowserCookies) -> Self {
+        self.cookies = Some(cookies.to_header());
+        self.auth_missing = cookies.validate().is_err();
+        self
+    }
+
+    /// Jar keeping cookies between runs. Cookies set with [`Self::cookies`] are
+    /// stored in it, replacing saved ones of the same name.
+    pub fn cookie_jar(mut self, jar: Arc<CookieJar>) -> Self {
+        self.config.cookie_jar = Some(jar);
+        self
+    }
+
+    /// Create new conversation.
+    pub async fn new_conversation(self, tone: Tone) -> Result<BingAIWs, SydneyError> {
+        if self.auth_missing {
+            return Err(SydneyError::MissingAuthCookie);
+        }
+
+        BingAIWs::create(self.config, tone, self.cookies).await
+    }
+
+    /// Image generator without a conversation.
+    pub fn image_creator(self) -> Result<ImageCreator, SydneyError> {
+        let client = build_client(&self.config, self.cookies)?;
+        Ok(ImageCreator::new(client, self.config.image_creator_url))
+    }
+
+    /// Reconnect to existing conversation exported with [`BingAIWs::export_state`].
+    pub fn resume(self, state: ConversationState) -> Result<BingAIWs, SydneyError> {
+        BingAIWs::resume_with_config(self.config, state, self.cookies)
+    }
+}
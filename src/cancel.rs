@@ -0,0 +1,58 @@
+use crate::sydney::send_ws_delim;
+use serde_json::json;
+use std::sync::{Arc, Mutex};
+use tokio::sync::Notify;
+use tokio_tungstenite::tungstenite::Message;
+
+/// Stops generation of a single answer, possibly from another task.
+///
+/// Obtained with [`ResponseStream::cancel_handle`](crate::ResponseStream::cancel_handle).
+/// After cancelling, the response stream ends and the conversation is ready for the
+/// next [`BingAIWs::ask`](crate::BingAIWs::ask).
+#[derive(Debug, Clone)]
+pub struct CancelHandle {
+    target: Arc<Mutex<CancelTarget>>,
+    notify: Arc<Notify>,
+}
+
+/// Invocation the cancel frame is sent for, updated when the answer's prompt is
+/// sent again (e.g. after rollover) on a new websocket.
+#[derive(Debug, Default)]
+struct CancelTarget {
+    tx: Option<futures_channel::mpsc::UnboundedSender<Message>>,
+    invocation_id: String,
+}
+
+impl CancelHandle {
+    pub(crate) fn new() -> Self {
+        Self {
+            target: Default::default(),
+            notify: Arc::new(Notify::new()),
+        }
+    }
+
+    pub(crate) fn set_target(
+        &self,
+        tx: Option<futures_channel::mpsc::UnboundedSender<Message>>,
+        invocation_id: String,
+    ) {
+        *self.target.lock().unwrap_or_else(|e| e.into_inner()) = CancelTarget { tx, invocation_id };
+    }
+
+    pub(crate) fn notify(&self) -> Arc<Notify> {
+        self.notify.clone()
+    }
+
+    /// Ask the server to stop the answer (SignalR `CancelInvocation`) and end the stream.
+    pub fn cancel(&self) {
+        let target = self.target.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some(tx) = &target.tx {
+            let frame = json!({
+                "type": 5,
+                "invocationId": target.invocation_id
+            });
+            _ = send_ws_delim(tx, frame);
+        }
+        self.notify.notify_one();
+    }
+}
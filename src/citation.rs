@@ -0,0 +1,206 @@
+use crate::protocol::SourceAttribution;
+use serde::{Deserialize, Serialize};
+use std::ops::Range;
+
+/// Source referenced in the answer text with `[^N^]` marker.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Citation {
+    /// Number used in the `[^N^]` markers (starts from 1).
+    pub index: usize,
+    pub title: String,
+    pub url: String,
+    /// Provider of the source, e.g. `search_web`.
+    pub provider: String,
+    pub image: Option<CitationImage>,
+}
+
+/// Image attached to the citation.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CitationImage {
+    pub url: String,
+    pub width: Option<u32>,
+    pub height: Option<u32>,
+    pub favicon: Option<String>,
+}
+
+/// `[^N^]` marker found in the answer text.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CitationMarker {
+    /// Number of the referenced [`Citation`].
+    pub index: usize,
+    /// Byte range of the whole marker in the text.
+    pub range: Range<usize>,
+}
+
+impl CitationMarker {
+    /// Find citation referenced by this marker.
+    pub fn citation<'a>(&self, citations: &'a [Citation]) -> Option<&'a Citation> {
+        citations.iter().find(|c| c.index == self.index)
+    }
+}
+
+impl Citation {
+    /// Convert `sourceAttributions` of the message, numbered from 1.
+    pub(crate) fn from_source_attributions(sources: &[SourceAttribution]) -> Vec<Self> {
+        sources
+            .iter()
+            .enumerate()
+            .map(|(i, source)| Self {
+                index: i + 1,
+                title: source.provider_display_name.clone().unwrap_or_default(),
+                url: source.see_more_url.clone().unwrap_or_default(),
+                provider: source.provider.clone().unwrap_or_default(),
+                image: source.image_link.clone().map(|url| CitationImage {
+                    url,
+                    width: source.image_width,
+                    height: source.image_height,
+                    favicon: source.image_favicon.clone(),
+                }),
+            })
+            .collect()
+    }
+}
+
+/// Find all `[^N^]` citation markers in the text.
+pub fn citation_markers(text: &str) -> Vec<CitationMarker> {
+    let mut markers = Vec::new();
+    let mut pos = 0;
+
+    while let Some(start) = text[pos..].find("[^").map(|i| i + pos) {
+        let digits_start = start + 2;
+        let digits_len = text[digits_start..]
+            .bytes()
+            .take_while(u8::is_ascii_digit)
+            .count();
+        let digits_end = digits_start + digits_len;
+
+        if digits_len > 0 && text[digits_end..].starts_with("^]") {
+            if let Ok(index) = text[digits_start..digits_end].parse() {
+                markers.push(CitationMarker {
+                    index,
+                    range: start..digits_end + 2,
+                });
+            }
+            pos = digits_end + 2;
+        } else {
+            pos = digits_start;
+        }
+    }
+
+    markers
+}
+
+/// Replace `[^N^]` markers with `[N]` and append footnotes with the urls of the used citations.
+pub fn render_footnotes(text: &str, citations: &[Citation]) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut used: Vec<&Citation> = Vec::new();
+    let mut last = 0;
+
+    for marker in citation_markers(text) {
+        out.push_str(&text[last..marker.range.start]);
+        out.push_str(&format!("[{}]", marker.index));
+        last = marker.range.end;
+
+        if let Some(citation) = marker.citation(citations) {
+            if !used.contains(&citation) {
+                used.push(citation);
+            }
+        }
+    }
+    out.push_str(&text[last..]);
+
+    if !used.is_empty() {
+        out.push_str("\n\n");
+        for citation in used {
+            out.push_str(&format!(
+                "[{}]: {} \"{}\"\n",
+                citation.index, citation.url, citation.title
+            ));
+        }
+    }
+
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn citation(index: usize) -> Citation {
+        Citation {
+            index,
+            title: format!("Source {index}"),
+            url: format!("https://example.com/{index}"),
+            provider: "search_web".to_string(),
+            image: None,
+        }
+    }
+
+    #[test]
+    fn finds_markers() {
+        let text = "Paris[^1^] is big[^12^][^2^].";
+        let markers = citation_markers(text);
+
+        assert_eq!(
+            markers,
+            vec![
+                CitationMarker {
+                    index: 1,
+                    range: 5..10
+                },
+                CitationMarker {
+                    index: 12,
+                    range: 17..23
+                },
+                CitationMarker {
+                    index: 2,
+                    range: 23..28
+                },
+            ]
+        );
+        assert_eq!(&text[markers[1].range.clone()], "[^12^]");
+    }
+
+    #[test]
+    fn skips_malformed_markers() {
+        assert!(citation_markers("[^x^] [^^] [^1 [^12").is_empty());
+        assert!(citation_markers("[^1]").is_empty());
+        assert!(citation_markers("[^99999999999999999999999^]").is_empty());
+
+        // Broken marker doesn't hide the next valid one
+        let markers = citation_markers("[^[^3^] é[^4^]");
+        assert_eq!(markers.iter().map(|m| m.index).collect::<Vec<_>>(), [3, 4]);
+        assert_eq!(markers[1].range, 10..15);
+    }
+
+    #[test]
+    fn marker_citation() {
+        let citations = [citation(1), citation(2)];
+        let markers = citation_markers("[^2^] [^3^]");
+        assert_eq!(markers[0].citation(&citations), Some(&citations[1]));
+        assert_eq!(markers[1].citation(&citations), None);
+    }
+
+    #[test]
+    fn footnotes_of_used_citations() {
+        let citations = [citation(1), citation(2), citation(3)];
+        let text = render_footnotes("Paris[^2^] is big[^1^][^2^][^7^].", &citations);
+
+        assert_eq!(
+            text,
+            "Paris[2] is big[1][2][7].\n\n\
+            [2]: https://example.com/2 \"Source 2\"\n\
+            [1]: https://example.com/1 \"Source 1\"\n"
+        );
+    }
+
+    #[test]
+    fn no_footnotes_without_markers() {
+        assert_eq!(
+            render_footnotes("Paris [^x^]", &[citation(1)]),
+            "Paris [^x^]"
+        );
+    }
+}
@@ -0,0 +1,117 @@
+use crate::store::write_private;
+use crate::sydney::SydneyError;
+use anyhow::anyhow;
+use cookie_store::RawCookie;
+use reqwest::header::HeaderValue;
+use reqwest::Url;
+use std::io::BufReader;
+use std::path::{Path, PathBuf};
+use std::sync::RwLock;
+use tracing::warn;
+
+/// Cookie jar saved to a json file, so cookies set by Bing survive between runs.
+///
+/// Loaded when opened and saved every time a response updates its cookies. The file
+/// is only readable by the user, since it contains the auth cookie.
+#[derive(Debug)]
+pub struct CookieJar {
+    path: PathBuf,
+    store: RwLock<cookie_store::CookieStore>,
+}
+
+impl CookieJar {
+    /// Open jar saved in the file, empty if it doesn't exist yet.
+    pub fn open(path: impl Into<PathBuf>) -> Result<Self, SydneyError> {
+        let path = path.into();
+
+        let store = match std::fs::File::open(&path) {
+            Ok(file) => cookie_store::CookieStore::load_json_all(BufReader::new(file))
+                .map_err(|e| anyhow!("Cannot load cookie jar {}: {e}", path.display()))?,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Default::default(),
+            Err(e) => return Err(e.into()),
+        };
+
+        Ok(Self {
+            path,
+            store: RwLock::new(store),
+        })
+    }
+
+    /// Jar in the user data directory, next to saved conversations.
+    #[cfg(feature = "dirs")]
+    pub fn open_default() -> Result<Self, SydneyError> {
+        Self::open(crate::store::data_dir()?.join("cookies.json"))
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Value of the cookie sent with requests to the url.
+    pub fn get(&self, url: &str, name: &str) -> Option<String> {
+        let url = Url::parse(url).ok()?;
+        let store = self.store.read().unwrap_or_else(|e| e.into_inner());
+        let value = store
+            .get_request_values(&url)
+            .find(|(n, _)| *n == name)
+            .map(|(_, value)| value.to_string());
+        value
+    }
+
+    /// Add cookies from raw `Cookie:` header value, replacing saved ones of the same name.
+    pub fn insert_header(&self, header: &str, url: &str) -> Result<(), SydneyError> {
+        let url = Url::parse(url).map_err(anyhow::Error::from)?;
+        let cookies = header
+            .split(';')
+            .filter_map(|pair| pair.trim().split_once('='))
+            .map(|(name, value)| {
+                RawCookie::build(name.to_string(), value.to_string())
+                    .path("/")
+                    .finish()
+            });
+
+        let mut store = self.store.write().unwrap_or_else(|e| e.into_inner());
+        store.store_response_cookies(cookies, &url);
+        self.save(&store)
+    }
+
+    fn save(&self, store: &cookie_store::CookieStore) -> Result<(), SydneyError> {
+        write_private(&self.path, |file| {
+            store
+                .save_incl_expired_and_nonpersistent_json(file)
+                .map_err(|e| anyhow!("Cannot save cookie jar: {e}").into())
+        })
+    }
+}
+
+impl reqwest::cookie::CookieStore for CookieJar {
+    fn set_cookies(&self, cookie_headers: &mut dyn Iterator<Item = &HeaderValue>, url: &Url) {
+        let cookies = cookie_headers
+            .filter_map(|value| value.to_str().ok())
+            .filter_map(|value| RawCookie::parse(value.to_string()).ok())
+            .collect::<Vec<_>>();
+        if cookies.is_empty() {
+            return;
+        }
+
+        let mut store = self.store.write().unwrap_or_else(|e| e.into_inner());
+        store.store_response_cookies(cookies.into_iter(), url);
+        if let Err(e) = self.save(&store) {
+            warn!("Cannot save cookie jar {}: {e}", self.path.display());
+        }
+    }
+
+    fn cookies(&self, url: &Url) -> Option<HeaderValue> {
+        let store = self.store.read().unwrap_or_else(|e| e.into_inner());
+        let header = store
+            .get_request_values(url)
+            .map(|(name, value)| format!("{name}={value}"))
+            .collect::<Vec<_>>()
+            .join("; ");
+
+        if header.is_empty() {
+            return None;
+        }
+        HeaderValue::from_str(&header).ok()
+    }
+}
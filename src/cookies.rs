@@ -0,0 +1,162 @@
+use crate::sydney::SydneyError;
+use anyhow::anyhow;
+use serde::Deserialize;
+use std::path::Path;
+
+/// Auth cookie of the signed in Bing account.
+const AUTH_COOKIE: &str = "_U";
+
+/// Single cookie from a browser export.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BrowserCookie {
+    pub domain: String,
+    pub name: String,
+    pub value: String,
+    pub path: String,
+    pub secure: bool,
+    /// Unix timestamp, `None` for session cookies.
+    pub expires: Option<u64>,
+}
+
+/// Bing cookies loaded from a browser export, Netscape `cookies.txt` or the json
+/// array written by cookie export extensions. Cookies of other domains are dropped.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct BrowserCookies {
+    cookies: Vec<BrowserCookie>,
+}
+
+/// Cookie in the json export format.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct JsonCookie {
+    #[serde(default)]
+    domain: String,
+    name: String,
+    value: String,
+    #[serde(default)]
+    path: Option<String>,
+    #[serde(default)]
+    secure: bool,
+    expiration_date: Option<f64>,
+}
+
+impl BrowserCookies {
+    /// Load file in any of the supported formats, also accepting raw `Cookie:` header value.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, SydneyError> {
+        let text = std::fs::read_to_string(path)?;
+        let trimmed = text.trim();
+
+        if trimmed.starts_with('[') {
+            Self::from_json(trimmed)
+        } else if trimmed.starts_with('#') || trimmed.contains('\t') {
+            Self::from_netscape(trimmed)
+        } else {
+            Ok(Self::from_header(trimmed))
+        }
+    }
+
+    /// Parse Netscape `cookies.txt`.
+    pub fn from_netscape(text: &str) -> Result<Self, SydneyError> {
+        let mut cookies = Vec::new();
+
+        for (i, line) in text.lines().enumerate() {
+            // HttpOnly cookies are written as comments with this prefix
+            let line = line.strip_prefix("#HttpOnly_").unwrap_or(line).trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let fields: Vec<&str> = line.split('\t').collect();
+            let [domain, _subdomains, path, secure, expires, name, value] = fields[..] else {
+                return Err(anyhow!("Invalid cookies.txt line {}", i + 1).into());
+            };
+
+            cookies.push(BrowserCookie {
+                domain: domain.to_string(),
+                name: name.to_string(),
+                value: value.to_string(),
+                path: path.to_string(),
+                secure: secure.eq_ignore_ascii_case("TRUE"),
+                expires: expires.parse().ok().filter(|&e| e > 0),
+            });
+        }
+
+        Ok(Self::bing_only(cookies))
+    }
+
+    /// Parse json array of cookies, as exported by browser extensions.
+    pub fn from_json(text: &str) -> Result<Self, SydneyError> {
+        let cookies: Vec<JsonCookie> = serde_json::from_str(text)?;
+        let cookies = cookies
+            .into_iter()
+            .map(|c| BrowserCookie {
+                domain: c.domain,
+                name: c.name,
+                value: c.value,
+                path: c.path.unwrap_or("/".to_string()),
+                secure: c.secure,
+                expires: c.expiration_date.map(|e| e as u64),
+            })
+            .collect();
+
+        Ok(Self::bing_only(cookies))
+    }
+
+    /// Parse raw `Cookie:` header value, all cookies are taken as bing.com ones.
+    pub fn from_header(header: &str) -> Self {
+        let cookies = header
+            .split(';')
+            .filter_map(|pair| pair.trim().split_once('='))
+            .map(|(name, value)| BrowserCookie {
+                domain: ".bing.com".to_string(),
+                name: name.to_string(),
+                value: value.to_string(),
+                path: "/".to_string(),
+                secure: true,
+                expires: None,
+            })
+            .collect();
+
+        Self { cookies }
+    }
+
+    pub fn cookies(&self) -> &[BrowserCookie] {
+        &self.cookies
+    }
+
+    pub fn get(&self, name: &str) -> Option<&str> {
+        self.cookies
+            .iter()
+            .find(|c| c.name == name)
+            .map(|c| c.value.as_str())
+    }
+
+    /// Check that the `_U` auth cookie is present.
+    pub fn validate(&self) -> Result<(), SydneyError> {
+        match self.get(AUTH_COOKIE) {
+            Some(value) if !value.is_empty() => Ok(()),
+            _ => Err(SydneyError::MissingAuthCookie),
+        }
+    }
+
+    /// Value of the `Cookie:` header.
+    pub fn to_header(&self) -> String {
+        self.cookies
+            .iter()
+            .map(|c| format!("{}={}", c.name, c.value))
+            .collect::<Vec<_>>()
+            .join("; ")
+    }
+
+    fn bing_only(cookies: Vec<BrowserCookie>) -> Self {
+        let cookies = cookies
+            .into_iter()
+            .filter(|c| {
+                let domain = c.domain.trim_start_matches('.');
+                domain == "bing.com" || domain.ends_with(".bing.com")
+            })
+            .collect();
+
+        Self { cookies }
+    }
+}
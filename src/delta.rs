@@ -0,0 +1,123 @@
+use crate::sydney::SydneyResponse;
+
+/// Turns cumulative answer snapshots into incremental updates.
+#[derive(Debug, Default)]
+pub(crate) struct DeltaTracker {
+    text: String,
+}
+
+impl DeltaTracker {
+    pub(crate) fn reset(&mut self) {
+        self.text.clear();
+    }
+
+    /// Compare snapshot with the previous one. Returns `None` if nothing changed.
+    pub(crate) fn update(&mut self, snapshot: String) -> Option<SydneyResponse> {
+        if snapshot == self.text {
+            return None;
+        }
+
+        let response = if let Some(appended) = snapshot.strip_prefix(self.text.as_str()) {
+            SydneyResponse::StreamDelta(appended.to_string())
+        } else {
+            let offset = common_prefix_len(&self.text, &snapshot);
+            SydneyResponse::StreamRewrite {
+                offset,
+                text: snapshot[offset..].to_string(),
+            }
+        };
+
+        self.text = snapshot;
+        Some(response)
+    }
+}
+
+/// Length in bytes of the common prefix, always on a char boundary.
+fn common_prefix_len(a: &str, b: &str) -> usize {
+    a.char_indices()
+        .zip(b.chars())
+        .find(|((_, ca), cb)| ca != cb)
+        .map(|((i, _), _)| i)
+        .unwrap_or_else(|| a.len().min(b.len()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn update(tracker: &mut DeltaTracker, snapshot: &str) -> Option<SydneyResponse> {
+        tracker.update(snapshot.to_string())
+    }
+
+    #[test]
+    fn appended_text() {
+        let mut tracker = DeltaTracker::default();
+        assert_eq!(
+            update(&mut tracker, "Par"),
+            Some(SydneyResponse::StreamDelta("Par".to_string()))
+        );
+        assert_eq!(
+            update(&mut tracker, "Paris"),
+            Some(SydneyResponse::StreamDelta("is".to_string()))
+        );
+        assert_eq!(update(&mut tracker, "Paris"), None);
+    }
+
+    #[test]
+    fn rewritten_text() {
+        let mut tracker = DeltaTracker::default();
+        update(&mut tracker, "The capital is Lyon");
+        assert_eq!(
+            update(&mut tracker, "The capital is Paris."),
+            Some(SydneyResponse::StreamRewrite {
+                offset: 15,
+                text: "Paris.".to_string()
+            })
+        );
+        assert_eq!(
+            update(&mut tracker, "The capital is Paris. It"),
+            Some(SydneyResponse::StreamDelta(" It".to_string()))
+        );
+    }
+
+    #[test]
+    fn shrinking_text() {
+        let mut tracker = DeltaTracker::default();
+        update(&mut tracker, "Paris [^1^");
+        assert_eq!(
+            update(&mut tracker, "Paris "),
+            Some(SydneyResponse::StreamRewrite {
+                offset: 6,
+                text: String::new()
+            })
+        );
+    }
+
+    #[test]
+    fn non_ascii_prefix() {
+        let mut tracker = DeltaTracker::default();
+        update(&mut tracker, "Zürich");
+        assert_eq!(
+            update(&mut tracker, "Zürcher"),
+            Some(SydneyResponse::StreamRewrite {
+                offset: 4,
+                text: "cher".to_string()
+            })
+        );
+
+        // Chars differing only in their last byte
+        assert_eq!(common_prefix_len("aé", "aè"), 1);
+        assert_eq!(common_prefix_len("日本", "日本語"), 6);
+    }
+
+    #[test]
+    fn reset_starts_over() {
+        let mut tracker = DeltaTracker::default();
+        update(&mut tracker, "Paris");
+        tracker.reset();
+        assert_eq!(
+            update(&mut tracker, "Berlin"),
+            Some(SydneyResponse::StreamDelta("Berlin".to_string()))
+        );
+    }
+}
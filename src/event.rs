@@ -0,0 +1,209 @@
+use crate::sydney::{SydneyError, SydneyResponse};
+use serde::{Deserialize, Serialize};
+
+/// Version of the [`Event`] json format, bumped on breaking changes.
+pub const EVENT_VERSION: u32 = 2;
+
+/// Versioned json form of a response or an error, for logging and piping
+/// events between processes.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Event {
+    pub version: u32,
+    #[serde(flatten)]
+    pub payload: EventPayload,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "event", content = "payload", rename_all = "camelCase")]
+pub enum EventPayload {
+    Response(SydneyResponse),
+    Error(ErrorInfo),
+}
+
+impl Event {
+    pub fn response(response: SydneyResponse) -> Self {
+        Self {
+            version: EVENT_VERSION,
+            payload: EventPayload::Response(response),
+        }
+    }
+
+    pub fn error(error: &SydneyError) -> Self {
+        Self {
+            version: EVENT_VERSION,
+            payload: EventPayload::Error(error.into()),
+        }
+    }
+}
+
+/// Serializable form of [`SydneyError`].
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ErrorInfo {
+    pub kind: ErrorKind,
+    pub message: String,
+}
+
+impl From<&SydneyError> for ErrorInfo {
+    fn from(error: &SydneyError) -> Self {
+        Self {
+            kind: error.kind(),
+            message: error.to_string(),
+        }
+    }
+}
+
+/// Variant of [`SydneyError`], without its data.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ErrorKind {
+    WebSocketNotConnected,
+    CreateConversationFailed,
+    HttpError,
+    WebSocketError,
+    JsonParsingError,
+    ProtocolError,
+    IoError,
+    Disconnected,
+    ServerError,
+    Timeout,
+    ImageUploadFailed,
+    ImageGenerationFailed,
+    ProxyError,
+    MissingAuthCookie,
+    MaxMessagesCountLimitReached,
+    CaptchaRequired,
+    Throttled,
+    EndOfResponse,
+    OtherError,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::citation::{Citation, CitationImage};
+    use serde_json::json;
+
+    #[test]
+    fn response_wire_shape() {
+        let event = Event::response(SydneyResponse::Quota { used: 7, max: 30 });
+        assert_eq!(
+            serde_json::to_value(&event).unwrap(),
+            json!({
+                "version": 2,
+                "event": "response",
+                "payload": { "type": "quota", "data": { "used": 7, "max": 30 } }
+            })
+        );
+
+        let event = Event::response(SydneyResponse::FinalText("Paris".to_string()));
+        assert_eq!(
+            serde_json::to_string(&event).unwrap(),
+            r#"{"version":2,"event":"response","payload":{"type":"finalText","data":"Paris"}}"#
+        );
+    }
+
+    #[test]
+    fn error_wire_shape() {
+        let event = Event::error(&SydneyError::ServerError("Internal error".to_string()));
+        assert_eq!(
+            serde_json::to_value(&event).unwrap(),
+            json!({
+                "version": 2,
+                "event": "error",
+                "payload": {
+                    "kind": "serverError",
+                    "message": "Server error: Internal error"
+                }
+            })
+        );
+
+        let event = Event::error(&SydneyError::MaxMessagesCountLimitReached);
+        assert_eq!(
+            serde_json::to_value(&event).unwrap()["payload"]["kind"],
+            "maxMessagesCountLimitReached"
+        );
+    }
+
+    #[test]
+    fn variant_wire_shapes() {
+        let cases = [
+            (
+                SydneyResponse::StreamRewrite {
+                    offset: 6,
+                    text: "Paris".to_string(),
+                },
+                json!({ "type": "streamRewrite", "data": { "offset": 6, "text": "Paris" } }),
+            ),
+            (
+                SydneyResponse::Rollover {
+                    previous_conversation_id: "a".to_string(),
+                    conversation_id: "b".to_string(),
+                },
+                json!({
+                    "type": "rollover",
+                    "data": { "previous_conversation_id": "a", "conversation_id": "b" }
+                }),
+            ),
+            (
+                SydneyResponse::ImageGenerationRequest {
+                    prompt: "a cat".to_string(),
+                },
+                json!({ "type": "imageGenerationRequest", "data": { "prompt": "a cat" } }),
+            ),
+            (
+                SydneyResponse::SuggestedResponses(vec!["More".to_string()]),
+                json!({ "type": "suggestedResponses", "data": ["More"] }),
+            ),
+        ];
+
+        for (response, expected) in cases {
+            assert_eq!(serde_json::to_value(&response).unwrap(), expected);
+        }
+    }
+
+    #[test]
+    fn every_response_round_trips() {
+        let responses = [
+            SydneyResponse::FinalText("Paris".to_string()),
+            SydneyResponse::StreamText("Par".to_string()),
+            SydneyResponse::StreamDelta("is".to_string()),
+            SydneyResponse::StreamRewrite {
+                offset: 3,
+                text: "is.".to_string(),
+            },
+            SydneyResponse::SuggestedResponses(vec!["And Germany?".to_string()]),
+            SydneyResponse::Sources(vec![Citation {
+                index: 1,
+                title: "Paris - Wikipedia".to_string(),
+                url: "https://en.wikipedia.org/wiki/Paris".to_string(),
+                provider: "search_web".to_string(),
+                image: Some(CitationImage {
+                    url: "https://example.com/paris.jpg".to_string(),
+                    width: Some(120),
+                    height: None,
+                    favicon: None,
+                }),
+            }]),
+            SydneyResponse::ImageGenerationRequest {
+                prompt: "a cat".to_string(),
+            },
+            SydneyResponse::Quota { used: 1, max: 30 },
+            SydneyResponse::Rollover {
+                previous_conversation_id: "a".to_string(),
+                conversation_id: "b".to_string(),
+            },
+        ];
+
+        for response in responses {
+            let event = Event::response(response);
+            let json = serde_json::to_string(&event).unwrap();
+            assert_eq!(serde_json::from_str::<Event>(&json).unwrap(), event);
+        }
+
+        let error = Event::error(&SydneyError::Disconnected);
+        let json = serde_json::to_string(&error).unwrap();
+        assert_eq!(serde_json::from_str::<Event>(&json).unwrap(), error);
+    }
+}
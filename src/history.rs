@@ -0,0 +1,79 @@
+use bing_ai_rust::{render_footnotes, SydneyError, TranscriptStore};
+use clap::Subcommand;
+
+#[derive(Subcommand)]
+pub enum HistoryCommand {
+    /// List recorded conversations
+    List,
+
+    /// Show all turns of the conversation
+    Show { conversation_id: String },
+
+    /// Search prompts and answers of all conversations
+    Search { query: String },
+
+    /// Delete recorded conversation
+    Delete { conversation_id: String },
+}
+
+pub fn run(command: HistoryCommand) -> Result<(), SydneyError> {
+    let store = TranscriptStore::open_default()?;
+
+    match command {
+        HistoryCommand::List => {
+            for transcript in store.list()? {
+                let first_prompt = transcript
+                    .turns
+                    .first()
+                    .map(|turn| one_line(&turn.prompt, 60))
+                    .unwrap_or_default();
+
+                println!(
+                    "{}  {} turns  {first_prompt}",
+                    transcript.conversation_id,
+                    transcript.turns.len()
+                );
+            }
+        }
+        HistoryCommand::Show { conversation_id } => {
+            let transcript = store
+                .load(&conversation_id)?
+                .ok_or_else(|| anyhow::anyhow!("Conversation {conversation_id} not found"))?;
+
+            for turn in transcript.turns {
+                println!("> {}\n", turn.prompt);
+                println!(
+                    "{}\n",
+                    render_footnotes(&turn.answer, &turn.sources).trim_end()
+                );
+            }
+        }
+        HistoryCommand::Search { query } => {
+            for hit in store.search(&query)? {
+                println!(
+                    "{} #{}  {}",
+                    hit.conversation_id,
+                    hit.turn_index + 1,
+                    one_line(&hit.turn.prompt, 60)
+                );
+            }
+        }
+        HistoryCommand::Delete { conversation_id } => {
+            if !store.remove(&conversation_id)? {
+                return Err(anyhow::anyhow!("Conversation {conversation_id} not found").into());
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// First line of the text, shortened to `max` chars.
+fn one_line(text: &str, max: usize) -> String {
+    let line = text.lines().next().unwrap_or_default();
+    if line.chars().count() > max {
+        format!("{}...", line.chars().take(max).collect::<String>())
+    } else {
+        line.to_string()
+    }
+}
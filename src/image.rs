@@ -0,0 +1,150 @@
+use crate::sydney::SydneyError;
+use crate::types::Tone;
+use base64::Engine;
+use serde::Deserialize;
+use serde_json::json;
+use std::path::{Path, PathBuf};
+
+const BLOB_URL: &str = "https://www.bing.com/images/blob";
+
+/// Image attached to a prompt with [`BingAIWs::ask_with_image`](crate::BingAIWs::ask_with_image).
+#[derive(Debug, Clone, PartialEq)]
+pub enum ImageInput {
+    /// Local file, uploaded to Bing.
+    Path(PathBuf),
+    /// Encoded image (png, jpeg, ...), uploaded to Bing.
+    Bytes(Vec<u8>),
+    /// Publicly reachable image, fetched by Bing.
+    Url(String),
+}
+
+impl ImageInput {
+    /// Url for `http(s)://` values, local path otherwise.
+    pub fn parse(value: &str) -> Self {
+        if value.starts_with("http://") || value.starts_with("https://") {
+            Self::Url(value.to_string())
+        } else {
+            Self::Path(value.into())
+        }
+    }
+}
+
+/// Same as [`ImageInput::parse`].
+impl From<&str> for ImageInput {
+    fn from(value: &str) -> Self {
+        Self::parse(value)
+    }
+}
+
+/// Same as [`ImageInput::parse`].
+impl From<String> for ImageInput {
+    fn from(value: String) -> Self {
+        Self::parse(&value)
+    }
+}
+
+impl From<&Path> for ImageInput {
+    fn from(path: &Path) -> Self {
+        Self::Path(path.to_path_buf())
+    }
+}
+
+impl From<PathBuf> for ImageInput {
+    fn from(path: PathBuf) -> Self {
+        Self::Path(path)
+    }
+}
+
+impl From<Vec<u8>> for ImageInput {
+    fn from(bytes: Vec<u8>) -> Self {
+        Self::Bytes(bytes)
+    }
+}
+
+/// Blob urls of an uploaded image, as sent in the ask message.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct ImageUrls {
+    pub image_url: String,
+    pub original_image_url: String,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct UploadResult {
+    blob_id: Option<String>,
+    processed_blob_id: Option<String>,
+}
+
+/// Upload image to the Bing image blob endpoint (`images/kblob`).
+pub(crate) async fn upload(
+    client: &reqwest::Client,
+    upload_url: &str,
+    image: ImageInput,
+    conversation_id: &str,
+    tone: Tone,
+) -> Result<ImageUrls, SydneyError> {
+    let mut knowledge_request = json!({
+        "imageInfo": {},
+        "knowledgeRequest": {
+            "invokedSkills": ["ImageById"],
+            "subscriptionId": "Bing.Chat.Multimodal",
+            "invokedSkillsRequestData": { "enableFaceBlur": true },
+            "convoData": {
+                "convoid": conversation_id,
+                "convotone": tone.to_str()
+            }
+        }
+    });
+
+    let bytes = match image {
+        ImageInput::Path(path) => Some(tokio::fs::read(path).await?),
+        ImageInput::Bytes(bytes) => Some(bytes),
+        ImageInput::Url(url) => {
+            knowledge_request["imageInfo"]["url"] = json!(url);
+            None
+        }
+    };
+
+    let mut form =
+        reqwest::multipart::Form::new().text("knowledgeRequest", knowledge_request.to_string());
+    if let Some(bytes) = bytes {
+        let encoded = base64::engine::general_purpose::STANDARD.encode(bytes);
+        form = form.text("imageBase64", encoded);
+    }
+
+    let res = client
+        .post(upload_url)
+        .header(
+            reqwest::header::REFERER,
+            "https://www.bing.com/search?q=Bing+AI",
+        )
+        .multipart(form)
+        .send()
+        .await?;
+
+    let status = res.status();
+    if !status.is_success() {
+        return Err(SydneyError::ImageUploadFailed(status.to_string()));
+    }
+
+    let result: UploadResult = res.json().await?;
+    let blob_id = result.blob_id.filter(|id| !id.is_empty());
+    let processed_blob_id = result.processed_blob_id.filter(|id| !id.is_empty());
+
+    match (blob_id, processed_blob_id) {
+        (None, None) => Err(SydneyError::ImageUploadFailed(
+            "No blob id in response".to_string(),
+        )),
+        (blob_id, processed_blob_id) => {
+            let original = blob_id
+                .clone()
+                .or(processed_blob_id.clone())
+                .unwrap_or_default();
+            let processed = processed_blob_id.or(blob_id).unwrap_or_default();
+            Ok(ImageUrls {
+                image_url: format!("{BLOB_URL}?bcid={processed}"),
+                original_image_url: format!("{BLOB_URL}?bcid={original}"),
+            })
+        }
+    }
+}
@@ -0,0 +1,160 @@
+use crate::sydney::SydneyError;
+use crate::timeout::{with_timeout, TimeoutKind};
+use reqwest::Url;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use tracing::debug;
+
+/// Generates images with Bing Image Creator, for prompts from
+/// [`SydneyResponse::ImageGenerationRequest`](crate::SydneyResponse::ImageGenerationRequest).
+///
+/// Get one with [`BingAIWs::image_creator`](crate::BingAIWs::image_creator), so it uses
+/// the same cookies, proxy and headers as the conversation.
+#[derive(Debug, Clone)]
+pub struct ImageCreator {
+    client: reqwest::Client,
+    url: String,
+    poll_interval: Duration,
+    timeout: Duration,
+}
+
+impl ImageCreator {
+    pub(crate) fn new(client: reqwest::Client, url: String) -> Self {
+        Self {
+            client,
+            url,
+            poll_interval: Duration::from_secs(2),
+            timeout: Duration::from_secs(300),
+        }
+    }
+
+    /// Delay between checks whether the images are ready.
+    pub fn set_poll_interval(&mut self, interval: Duration) {
+        self.poll_interval = interval;
+    }
+
+    /// Give up after this long with [`TimeoutKind::ImageGeneration`].
+    pub fn set_timeout(&mut self, timeout: Duration) {
+        self.timeout = timeout;
+    }
+
+    /// Generate images and return their urls.
+    pub async fn create(&self, prompt: &str) -> Result<Vec<String>, SydneyError> {
+        let id = match self.request(prompt, 4).await? {
+            Some(id) => id,
+            // Fallback used by the web client when the fast mode isn't available
+            None => self.request(prompt, 3).await?.ok_or_else(|| {
+                SydneyError::ImageGenerationFailed(format!("Prompt was rejected: {prompt}"))
+            })?,
+        };
+        debug!("Image generation request id: {id}");
+
+        let results_url = format!(
+            "{}/async/results/{id}?q={}",
+            self.url,
+            urlencoding::encode(prompt)
+        );
+
+        with_timeout(Some(self.timeout), TimeoutKind::ImageGeneration, async {
+            loop {
+                let res = self.client.get(&results_url).send().await?;
+                let status = res.status();
+                if !status.is_success() {
+                    return Err(SydneyError::ImageGenerationFailed(status.to_string()));
+                }
+
+                // Empty body or `errorMessage` while the images are still generated
+                let body = res.text().await?;
+                if !body.contains("errorMessage") {
+                    let urls = image_urls(&body);
+                    if !urls.is_empty() {
+                        return Ok(urls);
+                    }
+                }
+
+                tokio::time::sleep(self.poll_interval).await;
+            }
+        })
+        .await
+    }
+
+    /// Download images into `dir`, returns paths of the saved files.
+    pub async fn download(
+        &self,
+        urls: &[String],
+        dir: impl AsRef<Path>,
+    ) -> Result<Vec<PathBuf>, SydneyError> {
+        let dir = dir.as_ref();
+        tokio::fs::create_dir_all(dir).await?;
+
+        let mut paths = Vec::new();
+        for (i, url) in urls.iter().enumerate() {
+            let bytes = self
+                .client
+                .get(url)
+                .send()
+                .await?
+                .error_for_status()?
+                .bytes()
+                .await?;
+
+            let path = dir.join(file_name(url, i));
+            tokio::fs::write(&path, bytes).await?;
+            paths.push(path);
+        }
+
+        Ok(paths)
+    }
+
+    /// Start generation, returns its id if the request was accepted.
+    async fn request(&self, prompt: &str, rt: u8) -> Result<Option<String>, SydneyError> {
+        let url = format!(
+            "{}?q={}&rt={rt}&FORM=GENCRE",
+            self.url,
+            urlencoding::encode(prompt)
+        );
+        let res = self
+            .client
+            .post(url)
+            .form(&[("q", prompt), ("qs", "ds")])
+            .send()
+            .await?;
+
+        // Accepted request is redirected to the page with its id
+        Ok(res
+            .url()
+            .query_pairs()
+            .find(|(key, _)| key == "id")
+            .map(|(_, id)| id.into_owned()))
+    }
+}
+
+/// Generated image urls from the results page, without the thumbnail size.
+fn image_urls(html: &str) -> Vec<String> {
+    let mut urls: Vec<String> = Vec::new();
+    for src in html.split("src=\"").skip(1) {
+        let Some(url) = src.split('"').next() else {
+            continue;
+        };
+        let url = url.split("?w=").next().unwrap_or(url);
+        if (url.contains("/th/id/") || url.contains("/th?id=")) && !urls.iter().any(|u| u == url) {
+            urls.push(url.to_string());
+        }
+    }
+
+    urls
+}
+
+fn file_name(url: &str, index: usize) -> String {
+    let id = Url::parse(url).ok().and_then(|url| {
+        url.query_pairs()
+            .find(|(key, _)| key == "id")
+            .map(|(_, id)| id.into_owned())
+            .or_else(|| url.path_segments()?.next_back().map(|s| s.to_string()))
+    });
+
+    match id {
+        Some(id) if !id.is_empty() => format!("{}.jpg", crate::store::file_stem(&id)),
+        _ => format!("image-{}.jpg", index + 1),
+    }
+}
@@ -1,3 +1,4 @@
+use crate::image::ImageUrls;
 use crate::types::Tone;
 use serde_json::{json, Value};
 
@@ -43,6 +44,7 @@ pub fn ask_json(
     conversation_signature: &str,
     client_id: &str,
     conversation_id: &str,
+    image: Option<&ImageUrls>,
 ) -> Value {
     let mut options_sets = OPTIONS_SETS.to_vec();
     options_sets.extend(tone.to_options_set());
@@ -64,8 +66,8 @@ pub fn ask_json(
                 "inputMethod": "Keyboard",
                 "text": prompt,
                 "messageType": "Chat",
-                "imageUrl": null,
-                "originalImageUrl": null
+                "imageUrl": image.map(|i| &i.image_url),
+                "originalImageUrl": image.map(|i| &i.original_image_url)
               },
               "conversationSignature": conversation_signature,
               "participant": {
@@ -81,3 +83,14 @@ pub fn ask_json(
           "type": 4
     })
 }
+
+/// Add text of previous conversation to the ask message, as its context.
+pub fn add_context(ask_json: &mut Value, context: &str) {
+    ask_json["arguments"][0]["previousMessages"] = json!([{
+        "author": "user",
+        "description": context,
+        "contextType": "WebPage",
+        "messageType": "Context",
+        "messageId": "discover-web--page-ping-mriduna-----"
+    }]);
+}
@@ -0,0 +1,52 @@
+//! Unofficial async client for Bing AI (Sydney) chat.
+//!
+//! Create a conversation with [`BingAIWs::new_conversation`] and send prompts with
+//! [`BingAIWs::ask`], which returns a [`ResponseStream`] of the answer. Use
+//! [`BingAIWsBuilder`] to change endpoints, bundle version, user agent or headers.
+//!
+//! Features: `dirs` adds `open_default` to the stores in the user data directory,
+//! `cli` builds the `bing-ai` command line client.
+
+mod adaptive_card;
+mod builder;
+mod cancel;
+mod citation;
+mod cookie_jar;
+mod cookies;
+mod delta;
+mod event;
+mod image;
+mod image_creator;
+mod json;
+#[cfg(feature = "mock")]
+pub mod mock;
+mod protocol;
+mod proxy;
+mod reconnect;
+mod rollover;
+mod store;
+mod stream;
+mod sydney;
+mod throttle;
+mod timeout;
+mod transcript;
+mod types;
+
+pub use builder::BingAIWsBuilder;
+pub use cancel::CancelHandle;
+pub use citation::{citation_markers, render_footnotes, Citation, CitationImage, CitationMarker};
+pub use cookie_jar::CookieJar;
+pub use cookies::{BrowserCookie, BrowserCookies};
+pub use event::{ErrorInfo, ErrorKind, Event, EventPayload, EVENT_VERSION};
+pub use image::ImageInput;
+pub use image_creator::ImageCreator;
+pub use proxy::Proxy;
+pub use reconnect::ReconnectPolicy;
+pub use rollover::RolloverPolicy;
+pub use store::ConversationStore;
+pub use stream::ResponseStream;
+pub use sydney::{BingAIWs, SydneyError, SydneyResponse};
+pub use throttle::ThrottleInfo;
+pub use timeout::{TimeoutKind, Timeouts};
+pub use transcript::{SearchHit, Transcript, TranscriptStore, Turn};
+pub use types::{ConversationState, Tone};
@@ -1,65 +1,238 @@
-use crate::sydney::SydneyResponse;
-use anyhow::Result;
-use sydney::{BingAIWs, SydneyError};
-use tracing::{debug, error, info};
+use anyhow::{anyhow, Result};
+use ask::{AskOptions, OutputFormat};
+use bing_ai_rust::{
+    BingAIWs, BrowserCookies, ConversationStore, CookieJar, Event, ImageInput, Proxy,
+    ReconnectPolicy, RolloverPolicy, SydneyError, Tone, TranscriptStore,
+};
+use clap::{Args, Parser, Subcommand};
+use history::HistoryCommand;
+use repl::Repl;
+use std::io::Read;
+use std::path::PathBuf;
+use std::process::ExitCode;
+use std::sync::Arc;
+use tracing::error;
 
-mod json;
-mod sydney;
-mod types;
+mod ask;
+mod history;
+mod repl;
 
-#[tokio::main]
-async fn main() -> Result<()> {
-    _ = dotenvy::dotenv();
+#[derive(Parser)]
+#[command(name = "bing-ai", about = "Bing AI chat in the terminal")]
+struct Cli {
+    #[command(subcommand)]
+    command: Option<Command>,
+}
 
-    tracing_subscriber::fmt::init();
-    let cookies_str = std::env::var("COOKIES").ok();
+#[derive(Subcommand)]
+enum Command {
+    /// Interactive multi-turn chat (default)
+    Chat {
+        #[command(flatten)]
+        conn: ConnArgs,
+    },
 
-    let mut ai = BingAIWs::new_conversation(types::Tone::Precise, cookies_str).await?;
-    //ai.set_citations(true);
-    ai.set_close_ws_after(true);
+    /// Ask single prompt and print the answer
+    Ask {
+        /// Prompt to send, read from stdin if not given or "-"
+        prompt: Option<String>,
 
-    ai.ask("What is the capital of France?").await?;
+        #[command(flatten)]
+        conn: ConnArgs,
 
-    /*
-    let resp = ai.get_final_response().await?;
-    info!("resp: {resp}");
-    */
+        /// Include citations in the answer
+        #[arg(long)]
+        citations: bool,
 
-    loop {
-        let res = ai.get_next_msgs().await;
-        match res {
-            Err(SydneyError::EndOfResponse) => {
-                debug!("End of response");
-                break;
-            }
-            Err(e) => {
-                error!("Error: {}", e);
-                break;
+        /// Include suggested responses
+        #[arg(long)]
+        suggestions: bool,
+
+        /// Output format
+        #[arg(short, long, value_enum, default_value_t = OutputFormat::Text)]
+        format: OutputFormat,
+
+        /// Image to ask about, local file or http(s) url
+        #[arg(short, long, value_name = "PATH|URL")]
+        image: Option<String>,
+
+        /// Download images generated in the answer into this directory
+        #[arg(long, value_name = "DIR")]
+        images_dir: Option<PathBuf>,
+    },
+
+    /// Browse recorded conversations
+    History {
+        #[command(subcommand)]
+        command: HistoryCommand,
+    },
+}
+
+#[derive(Args, Default)]
+struct ConnArgs {
+    /// Conversation tone (precise, creative or balanced) [default: precise]
+    #[arg(short, long)]
+    tone: Option<Tone>,
+
+    /// Cookies exported from the browser (cookies.txt, json array or raw `Cookie:` header)
+    /// [default: raw header in COOKIES env var, or cookies saved by previous runs]
+    #[arg(short, long)]
+    cookie_file: Option<PathBuf>,
+
+    /// Resume saved conversation with this id ("last" for the most recent one)
+    #[arg(short, long, value_name = "ID")]
+    resume: Option<String>,
+
+    /// Continue in a new conversation, with recent context, when the message limit is reached
+    #[arg(long)]
+    rollover: bool,
+
+    /// Proxy url (http://, socks5:// or socks5h://) [default: HTTPS_PROXY or ALL_PROXY env var]
+    #[arg(long, value_name = "URL")]
+    proxy: Option<String>,
+}
+
+impl ConnArgs {
+    fn cookies(&self) -> Result<Option<BrowserCookies>> {
+        match &self.cookie_file {
+            Some(path) => Ok(Some(BrowserCookies::load(path)?)),
+            None => Ok(std::env::var("COOKIES")
+                .ok()
+                .map(|header| BrowserCookies::from_header(&header))),
+        }
+    }
+
+    /// Create new conversation or resume the saved one, with transcript recording enabled.
+    async fn connect(&self, cookies: Option<BrowserCookies>) -> Result<BingAIWs, SydneyError> {
+        let mut ai = self.create_or_resume(cookies).await?;
+        ai.set_transcript_store(Some(TranscriptStore::open_default()?));
+        ai.set_reconnect_policy(Some(ReconnectPolicy::default()));
+        if self.rollover {
+            ai.set_rollover_policy(Some(RolloverPolicy::default()));
+        }
+
+        Ok(ai)
+    }
+
+    async fn create_or_resume(
+        &self,
+        cookies: Option<BrowserCookies>,
+    ) -> Result<BingAIWs, SydneyError> {
+        let mut builder = BingAIWs::builder().cookie_jar(Arc::new(CookieJar::open_default()?));
+        if let Some(cookies) = &cookies {
+            builder = builder.browser_cookies(cookies);
+        }
+        if let Some(proxy) = &self.proxy {
+            builder = builder.proxy(Proxy::new(proxy)?);
+        }
+
+        let Some(id) = &self.resume else {
+            return builder
+                .new_conversation(self.tone.unwrap_or(Tone::Precise))
+                .await;
+        };
+
+        let store = ConversationStore::open_default()?;
+        let state = match id.as_str() {
+            "last" => store.latest()?,
+            id => store.load(id)?,
+        }
+        .ok_or_else(|| anyhow!("Saved conversation {id} not found"))?;
+
+        let mut ai = builder.resume(state)?;
+        if let Some(tone) = self.tone {
+            ai.set_tone(tone);
+        }
+
+        Ok(ai)
+    }
+}
+
+#[tokio::main]
+async fn main() -> ExitCode {
+    _ = dotenvy::dotenv();
+
+    tracing_subscriber::fmt()
+        .with_writer(std::io::stderr)
+        .init();
+    let cli = Cli::parse();
+
+    let command = cli.command.unwrap_or(Command::Chat {
+        conn: ConnArgs::default(),
+    });
+
+    match command {
+        Command::Chat { conn } => {
+            if let Err(e) = chat(conn).await {
+                error!("Error: {e}");
+                return ExitCode::FAILURE;
             }
-            Ok(msgs) => {
-                for msg in msgs {
-                    info!("Stream response: {:?}", msg);
-
-                    match msg {
-                        SydneyResponse::Sources(sources) => {
-                            info!("Sources: {:?}", sources);
-                        }
-                        SydneyResponse::FinalText(text) => {
-                            info!("FINAL TEXT:\n\n{}\n\n", text);
-                        }
-                        _ => {}
+        }
+        Command::Ask {
+            prompt,
+            conn,
+            citations,
+            suggestions,
+            format,
+            image,
+            images_dir,
+        } => {
+            let opts = AskOptions {
+                citations,
+                suggestions,
+                format,
+                image: image.as_deref().map(ImageInput::parse),
+                images_dir,
+            };
+
+            if let Err(e) = ask(prompt, &conn, opts).await {
+                error!("Error: {e}");
+                if format == OutputFormat::Json {
+                    if let Ok(event) = serde_json::to_string(&Event::error(&e)) {
+                        println!("{event}");
                     }
                 }
+                return ExitCode::from(ask::exit_code(&e));
+            }
+        }
+        Command::History { command } => {
+            if let Err(e) = history::run(command) {
+                error!("Error: {e}");
+                return ExitCode::from(ask::exit_code(&e));
             }
         }
     }
 
-    /*
-    ai.ask("What is my name? (Respond with fake paris name)")
-        .await?;
-    ai.get_next_msg().await?;
-    */
+    ExitCode::SUCCESS
+}
+
+async fn chat(conn: ConnArgs) -> Result<()> {
+    let ai = conn.connect(conn.cookies()?).await?;
+
+    let mut repl = Repl::new(ai, ConversationStore::open_default()?);
+    repl.run().await
+}
+
+async fn ask(prompt: Option<String>, conn: &ConnArgs, opts: AskOptions) -> Result<(), SydneyError> {
+    let prompt = read_prompt(prompt)?;
+    let ai = conn.connect(conn.cookies()?).await?;
+    ask::ask(ai, &prompt, opts).await
+}
+
+fn read_prompt(prompt: Option<String>) -> Result<String> {
+    let prompt = match prompt {
+        Some(prompt) if prompt != "-" => prompt,
+        _ => {
+            let mut buf = String::new();
+            std::io::stdin().read_to_string(&mut buf)?;
+            buf
+        }
+    };
+
+    let prompt = prompt.trim();
+    if prompt.is_empty() {
+        return Err(anyhow!("Empty prompt"));
+    }
 
-    //tokio::signal::ctrl_c().await?;
-    Ok(())
+    Ok(prompt.to_string())
 }
@@ -0,0 +1,597 @@
+//! Local ChatHub server for offline tests, enabled with the `mock` feature.
+//!
+//! Emulates `turing/conversation/create` and `sydney/ChatHub` on a random local port
+//! and answers every prompt with the next scripted list of [`MockAction`]s.
+//!
+//! ```no_run
+//! # async fn run() -> Result<(), bing_ai_rust::SydneyError> {
+//! use bing_ai_rust::mock::{MockAction, MockServer};
+//! use bing_ai_rust::Tone;
+//!
+//! let server = MockServer::start().await?;
+//! server.push_answer(vec![
+//!     MockAction::Update("Par".to_string()),
+//!     MockAction::Final("Paris".to_string()),
+//! ]);
+//!
+//! let mut ai = server.new_conversation(Tone::Precise).await?;
+//! let answer = ai.ask("What is the capital of France?").await?.final_text().await?;
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::builder::BingAIWsBuilder;
+use crate::sydney::{BingAIWs, SydneyError};
+use crate::types::{ConversationState, Tone};
+use futures_util::{SinkExt, StreamExt};
+use serde_json::{json, Value};
+use std::collections::VecDeque;
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::task::JoinHandle;
+use tokio_tungstenite::tungstenite::Message;
+use tokio_tungstenite::WebSocketStream;
+
+const DELIMETER: char = '\x1E';
+
+/// Single step of the scripted answer.
+#[derive(Debug, Clone)]
+pub enum MockAction {
+    /// Type 1 frame with the answer generated so far.
+    Update(String),
+    /// Type 2 frame with the final answer.
+    Final(String),
+    /// Raw frame sent as is.
+    Frame(Value),
+    /// Type 3 frame for the current invocation.
+    Completion {
+        error: Option<String>,
+    },
+    /// Type 6 keep-alive frame.
+    Ping,
+    /// Type 7 frame.
+    Close {
+        error: Option<String>,
+        allow_reconnect: bool,
+    },
+    /// Drop the connection without closing handshake.
+    Disconnect,
+    Delay(Duration),
+}
+
+impl MockAction {
+    /// Type 2 frame of throttled request, with retry hint of 60 seconds.
+    pub fn throttled() -> Self {
+        let mut frame = result_without_messages("Throttled", "Request is throttled.");
+        frame["item"]["result"]["retryAfter"] = json!(60);
+        Self::Frame(frame)
+    }
+
+    /// Type 2 frame asking to solve captcha.
+    pub fn captcha() -> Self {
+        Self::Frame(result_without_messages(
+            "CaptchaChallenge",
+            "User needs to solve CAPTCHA to continue.",
+        ))
+    }
+
+    /// Type 1 frame asking to generate image (Creative tone).
+    pub fn generate_image(prompt: &str) -> Self {
+        Self::Frame(json!({
+            "type": 1,
+            "target": "update",
+            "arguments": [{
+                "messages": [{
+                    "text": prompt,
+                    "author": "bot",
+                    "messageType": "GenerateContentQuery",
+                    "contentType": "IMAGE"
+                }]
+            }]
+        }))
+    }
+
+    /// Type 2 frame with the answer to the last allowed message of the conversation.
+    pub fn max_messages(max: i64, text: &str) -> Self {
+        let mut frame = final_frame("0", text, max);
+        frame["item"]["throttling"]["maxNumUserMessagesInConversation"] = json!(max);
+        Self::Frame(frame)
+    }
+
+    /// Type 2 frame rejecting the prompt without answer, conversation is full.
+    pub fn limit_reached() -> Self {
+        Self::Frame(json!({
+            "type": 2,
+            "invocationId": "0",
+            "item": {
+                "messages": [],
+                "result": { "value": "Success" },
+                "throttling": {
+                    "numUserMessagesInConversation": 30,
+                    "maxNumUserMessagesInConversation": 30
+                }
+            }
+        }))
+    }
+}
+
+/// Request line target and headers of a received request.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MockRequest {
+    /// Path with the query string.
+    pub path: String,
+    pub headers: Vec<(String, String)>,
+}
+
+impl MockRequest {
+    fn parse(head: &str) -> Self {
+        let mut lines = head.lines();
+        let path = lines
+            .next()
+            .and_then(|line| line.split_whitespace().nth(1))
+            .unwrap_or_default()
+            .to_string();
+        let headers = lines
+            .filter_map(|line| line.split_once(':'))
+            .map(|(name, value)| (name.to_string(), value.trim().to_string()))
+            .collect();
+
+        Self { path, headers }
+    }
+
+    /// Value of the header, name is case insensitive.
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(n, _)| n.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_str())
+    }
+}
+
+#[derive(Default)]
+struct State {
+    answers: VecDeque<Vec<MockAction>>,
+    prompts: Vec<String>,
+    client_frames: Vec<Value>,
+    conversations: usize,
+    create_requests: Vec<MockRequest>,
+    handshakes: Vec<MockRequest>,
+    create_result: Option<String>,
+    delay: Duration,
+    uploads: Vec<String>,
+    image_prompts: Vec<String>,
+    image_polls: usize,
+}
+
+/// Running mock server, stopped when dropped.
+pub struct MockServer {
+    addr: SocketAddr,
+    state: Arc<Mutex<State>>,
+    task: JoinHandle<()>,
+}
+
+impl MockServer {
+    pub async fn start() -> Result<Self, SydneyError> {
+        let listener = TcpListener::bind("127.0.0.1:0").await?;
+        let addr = listener.local_addr()?;
+        let state = Arc::new(Mutex::new(State::default()));
+
+        let task = tokio::spawn({
+            let state = state.clone();
+            async move {
+                while let Ok((stream, _)) = listener.accept().await {
+                    tokio::spawn(handle_conn(stream, state.clone()));
+                }
+            }
+        });
+
+        Ok(Self { addr, state, task })
+    }
+
+    pub fn create_url(&self) -> String {
+        format!("http://{}/turing/conversation/create", self.addr)
+    }
+
+    pub fn ws_url(&self) -> String {
+        format!("ws://{}/sydney/ChatHub", self.addr)
+    }
+
+    pub fn image_upload_url(&self) -> String {
+        format!("http://{}/images/kblob", self.addr)
+    }
+
+    pub fn image_creator_url(&self) -> String {
+        format!("http://{}/images/create", self.addr)
+    }
+
+    /// Client builder pointed at this server.
+    pub fn builder(&self) -> BingAIWsBuilder {
+        BingAIWsBuilder::new()
+            .create_url(self.create_url())
+            .ws_url(self.ws_url())
+            .image_upload_url(self.image_upload_url())
+            .image_creator_url(self.image_creator_url())
+            .no_proxy()
+    }
+
+    /// Create conversation on this server.
+    pub async fn new_conversation(&self, tone: Tone) -> Result<BingAIWs, SydneyError> {
+        self.builder().new_conversation(tone).await
+    }
+
+    /// Resume conversation on this server.
+    pub fn resume(&self, state: ConversationState) -> Result<BingAIWs, SydneyError> {
+        self.builder().resume(state)
+    }
+
+    /// Queue actions used to answer the next prompt. Prompts without queued
+    /// answer are left unanswered.
+    pub fn push_answer(&self, actions: Vec<MockAction>) {
+        self.state().answers.push_back(actions);
+    }
+
+    /// Make create requests fail with this result value (`None` for success).
+    pub fn set_create_result(&self, value: Option<&str>) {
+        self.state().create_result = value.map(|v| v.to_string());
+    }
+
+    /// Delay answering create requests and websocket handshakes.
+    pub fn set_delay(&self, delay: Duration) {
+        self.state().delay = delay;
+    }
+
+    /// Prompts received so far.
+    pub fn prompts(&self) -> Vec<String> {
+        self.state().prompts.clone()
+    }
+
+    /// All frames sent by clients, except the handshake.
+    pub fn client_frames(&self) -> Vec<Value> {
+        self.state().client_frames.clone()
+    }
+
+    /// Raw bodies of image upload requests.
+    pub fn uploads(&self) -> Vec<String> {
+        self.state().uploads.clone()
+    }
+
+    /// Prompts of image generation requests.
+    pub fn image_prompts(&self) -> Vec<String> {
+        self.state().image_prompts.clone()
+    }
+
+    pub fn conversations_created(&self) -> usize {
+        self.state().conversations
+    }
+
+    /// `Cookie:` header of every create request.
+    pub fn create_cookies(&self) -> Vec<Option<String>> {
+        self.state()
+            .create_requests
+            .iter()
+            .map(|request| request.header("cookie").map(|c| c.to_string()))
+            .collect()
+    }
+
+    /// Every create request received so far.
+    pub fn create_requests(&self) -> Vec<MockRequest> {
+        self.state().create_requests.clone()
+    }
+
+    /// Every websocket handshake request received so far.
+    pub fn handshakes(&self) -> Vec<MockRequest> {
+        self.state().handshakes.clone()
+    }
+
+    fn state(&self) -> MutexGuard<'_, State> {
+        lock(&self.state)
+    }
+}
+
+impl Drop for MockServer {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
+
+fn lock(state: &Mutex<State>) -> MutexGuard<'_, State> {
+    state.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+async fn handle_conn(mut stream: TcpStream, state: Arc<Mutex<State>>) {
+    let mut buf = [0u8; 4096];
+
+    // Peek the request head, so websocket handshake can still read it
+    let head_len = loop {
+        let n = match stream.peek(&mut buf).await {
+            Ok(0) | Err(_) => return,
+            Ok(n) => n,
+        };
+
+        if let Some(pos) = buf[..n].windows(4).position(|w| w == b"\r\n\r\n") {
+            break pos + 4;
+        }
+        if n == buf.len() {
+            return;
+        }
+        tokio::time::sleep(Duration::from_millis(1)).await;
+    };
+
+    let delay = lock(&state).delay;
+    tokio::time::sleep(delay).await;
+
+    let head = String::from_utf8_lossy(&buf[..head_len]).to_string();
+    let path = head.split_whitespace().nth(1).unwrap_or_default();
+
+    if path.starts_with("/sydney/ChatHub") {
+        lock(&state).handshakes.push(MockRequest::parse(&head));
+        if let Ok(ws) = tokio_tungstenite::accept_async(stream).await {
+            handle_ws(ws, state).await;
+        }
+        return;
+    }
+
+    if stream.read_exact(&mut buf[..head_len]).await.is_err() {
+        return;
+    }
+
+    let content_length = header(&head, "content-length")
+        .and_then(|value| value.parse::<usize>().ok())
+        .unwrap_or_default();
+    let mut body = vec![0u8; content_length];
+    if stream.read_exact(&mut body).await.is_err() {
+        return;
+    }
+
+    let method = head.split_whitespace().next().unwrap_or_default();
+    let addr = stream
+        .local_addr()
+        .map(|a| a.to_string())
+        .unwrap_or_default();
+
+    let response = if path.starts_with("/turing/conversation/create") {
+        lock(&state).create_requests.push(MockRequest::parse(&head));
+        create_response(&state)
+    } else if path.starts_with("/images/kblob") {
+        lock(&state)
+            .uploads
+            .push(String::from_utf8_lossy(&body).to_string());
+        json_response(&json!({
+            "blobId": "mock-blob",
+            "processedBlobId": "mock-processed-blob"
+        }))
+    } else if path.starts_with("/images/create/async/results/") {
+        image_results_response(&state, &addr)
+    } else if path.starts_with("/images/create") && method == "POST" {
+        image_create_response(&state, path)
+    } else if path.starts_with("/images/create") {
+        "HTTP/1.1 200 OK\r\nContent-Length: 0\r\nConnection: close\r\n\r\n".to_string()
+    } else if path.starts_with("/th/id/") {
+        "HTTP/1.1 200 OK\r\nContent-Type: image/jpeg\r\nContent-Length: 10\r\nConnection: close\r\n\r\nmock image"
+            .to_string()
+    } else {
+        "HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\nConnection: close\r\n\r\n".to_string()
+    };
+    _ = stream.write_all(response.as_bytes()).await;
+    _ = stream.shutdown().await;
+}
+
+fn header<'a>(head: &'a str, name: &str) -> Option<&'a str> {
+    head.lines().find_map(|line| {
+        let (n, value) = line.split_once(':')?;
+        n.eq_ignore_ascii_case(name).then_some(value.trim())
+    })
+}
+
+/// Create response also refreshes the session cookie, like Bing does.
+fn create_response(state: &Mutex<State>) -> String {
+    let mut state = lock(state);
+    state.conversations += 1;
+
+    let result = state.create_result.clone().unwrap_or("Success".to_string());
+    let body = json!({
+        "conversationId": format!("mock-conversation-{}", state.conversations),
+        "clientId": "mock-client",
+        "result": {
+            "value": result,
+            "message": null
+        }
+    })
+    .to_string();
+
+    format!(
+        "HTTP/1.1 200 OK\r\n\
+        Content-Type: application/json\r\n\
+        Content-Length: {}\r\n\
+        X-Sydney-ConversationSignature: mock-signature\r\n\
+        X-Sydney-EncryptedConversationSignature: mock-encrypted-signature\r\n\
+        Set-Cookie: SRCHSESS=mock-session-{}; Path=/; Max-Age=86400\r\n\
+        Connection: close\r\n\r\n{body}",
+        body.len(),
+        state.conversations
+    )
+}
+
+/// Redirect accepted generation to the page with its id, prompts containing
+/// "rejected" stay on the create page like blocked prompts.
+fn image_create_response(state: &Mutex<State>, path: &str) -> String {
+    let query = path.split_once('?').map(|(_, q)| q).unwrap_or_default();
+    let prompt = query
+        .split('&')
+        .find_map(|pair| pair.strip_prefix("q="))
+        .and_then(|q| urlencoding::decode(q).ok())
+        .unwrap_or_default()
+        .to_string();
+
+    let rejected = prompt.contains("rejected");
+    lock(state).image_prompts.push(prompt);
+    if rejected {
+        return "HTTP/1.1 200 OK\r\nContent-Length: 0\r\nConnection: close\r\n\r\n".to_string();
+    }
+
+    format!(
+        "HTTP/1.1 302 Found\r\n\
+        Location: /images/create?{query}&id=mock-image-request\r\n\
+        Content-Length: 0\r\n\
+        Connection: close\r\n\r\n"
+    )
+}
+
+/// Pending on the first poll, two images afterwards.
+fn image_results_response(state: &Mutex<State>, addr: &str) -> String {
+    let mut state = lock(state);
+    state.image_polls += 1;
+    let body = match state.image_polls {
+        1 => json!({ "errorMessage": "Pending" }).to_string(),
+        _ => (1..=2)
+            .map(|i| {
+                format!(
+                    "<img class=\"mimg\" src=\"http://{addr}/th/id/OIG.mock{i}?w=270&amp;h=270\" />"
+                )
+            })
+            .collect(),
+    };
+
+    format!(
+        "HTTP/1.1 200 OK\r\n\
+        Content-Type: text/html\r\n\
+        Content-Length: {}\r\n\
+        Connection: close\r\n\r\n{body}",
+        body.len()
+    )
+}
+
+fn json_response(body: &Value) -> String {
+    let body = body.to_string();
+    format!(
+        "HTTP/1.1 200 OK\r\n\
+        Content-Type: application/json\r\n\
+        Content-Length: {}\r\n\
+        Connection: close\r\n\r\n{body}",
+        body.len()
+    )
+}
+
+async fn handle_ws(ws: WebSocketStream<TcpStream>, state: Arc<Mutex<State>>) {
+    let (mut write, mut read) = ws.split();
+
+    while let Some(Ok(msg)) = read.next().await {
+        let Message::Text(text) = msg else {
+            continue;
+        };
+
+        for frame in text.split(DELIMETER).filter(|f| !f.is_empty()) {
+            let Ok(frame) = serde_json::from_str::<Value>(frame) else {
+                continue;
+            };
+
+            if frame.get("protocol").is_some() {
+                if write
+                    .send(Message::Text(format!("{{}}{DELIMETER}")))
+                    .await
+                    .is_err()
+                {
+                    return;
+                }
+                continue;
+            }
+
+            let actions = {
+                let mut state = lock(&state);
+                state.client_frames.push(frame.clone());
+                if frame["type"] != 4 {
+                    continue;
+                }
+
+                let prompt = frame["arguments"][0]["message"]["text"]
+                    .as_str()
+                    .unwrap_or_default();
+                state.prompts.push(prompt.to_string());
+                state.answers.pop_front().unwrap_or_default()
+            };
+
+            let invocation_id = frame["invocationId"].as_str().unwrap_or("0");
+            let messages_count = lock(&state).prompts.len() as i64;
+
+            for action in actions {
+                let frame = match action {
+                    MockAction::Update(text) => update_frame(&text),
+                    MockAction::Final(text) => final_frame(invocation_id, &text, messages_count),
+                    MockAction::Frame(frame) => frame,
+                    MockAction::Completion { error } => json!({
+                        "type": 3,
+                        "invocationId": invocation_id,
+                        "error": error
+                    }),
+                    MockAction::Ping => json!({ "type": 6 }),
+                    MockAction::Close {
+                        error,
+                        allow_reconnect,
+                    } => json!({
+                        "type": 7,
+                        "error": error,
+                        "allowReconnect": allow_reconnect
+                    }),
+                    MockAction::Disconnect => return,
+                    MockAction::Delay(delay) => {
+                        tokio::time::sleep(delay).await;
+                        continue;
+                    }
+                };
+
+                let msg = Message::Text(format!("{frame}{DELIMETER}"));
+                if write.send(msg).await.is_err() {
+                    return;
+                }
+            }
+        }
+    }
+}
+
+fn bot_message(text: &str) -> Value {
+    json!({
+        "text": text,
+        "author": "bot",
+        "adaptiveCards": [{
+            "type": "AdaptiveCard",
+            "version": "1.0",
+            "body": [{ "type": "TextBlock", "text": text, "wrap": true }]
+        }]
+    })
+}
+
+fn update_frame(text: &str) -> Value {
+    json!({
+        "type": 1,
+        "target": "update",
+        "arguments": [{ "messages": [bot_message(text)] }]
+    })
+}
+
+fn final_frame(invocation_id: &str, text: &str, messages_count: i64) -> Value {
+    json!({
+        "type": 2,
+        "invocationId": invocation_id,
+        "item": {
+            "messages": [bot_message(text)],
+            "result": { "value": "Success", "message": text },
+            "throttling": {
+                "maxNumUserMessagesInConversation": 30,
+                "numUserMessagesInConversation": messages_count
+            }
+        }
+    })
+}
+
+fn result_without_messages(value: &str, message: &str) -> Value {
+    json!({
+        "type": 2,
+        "invocationId": "0",
+        "item": {
+            "result": { "value": value, "message": message }
+        }
+    })
+}
@@ -0,0 +1,308 @@
+//! Serde model of the ChatHub frames (SignalR json hub protocol).
+//! Every change of the Bing message format should only need changes here.
+
+use crate::sydney::SydneyError;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Deserializer};
+
+/// Single frame, separated from others by the record separator.
+#[derive(Debug)]
+pub(crate) enum Frame {
+    /// Type 1, streamed part of the answer.
+    Update(UpdateFrame),
+    /// Type 2, result of the invocation with the final messages.
+    Result(ResultFrame),
+    /// Type 3, end of the invocation (with error if it failed).
+    Completion(CompletionFrame),
+    /// Type 6, keep-alive.
+    Ping,
+    /// Type 7, server is closing the connection.
+    Close(CloseFrame),
+    Unknown(i64),
+}
+
+impl Frame {
+    pub(crate) fn parse(frame: &str) -> Result<Self, SydneyError> {
+        let header: FrameHeader = parse(frame, "frame header")?;
+
+        Ok(match header.kind {
+            1 => Self::Update(parse(frame, "update")?),
+            2 => Self::Result(parse(frame, "result")?),
+            3 => Self::Completion(parse(frame, "completion")?),
+            6 => Self::Ping,
+            7 => Self::Close(parse(frame, "close")?),
+            kind => Self::Unknown(kind),
+        })
+    }
+}
+
+fn parse<'a, T: Deserialize<'a>>(frame: &'a str, name: &'static str) -> Result<T, SydneyError> {
+    serde_json::from_str(frame).map_err(|source| SydneyError::ProtocolError {
+        frame: name,
+        source,
+    })
+}
+
+#[derive(Debug, Deserialize)]
+struct FrameHeader {
+    #[serde(rename = "type")]
+    kind: i64,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct UpdateFrame {
+    #[serde(default)]
+    pub arguments: Vec<UpdateArguments>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct UpdateArguments {
+    pub messages: Option<Vec<ChatMessage>>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct ResultFrame {
+    pub item: ResultItem,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct ResultItem {
+    pub messages: Option<Vec<ChatMessage>>,
+    pub result: Option<ResultStatus>,
+    pub throttling: Option<Throttling>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct ResultStatus {
+    pub value: Option<String>,
+    pub message: Option<String>,
+    /// Seconds to wait before asking again.
+    #[serde(default, alias = "retryAfterSeconds", deserialize_with = "lenient_u32")]
+    pub retry_after: Option<u32>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct Throttling {
+    #[serde(default)]
+    pub num_user_messages_in_conversation: u32,
+    pub max_num_user_messages_in_conversation: u32,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct CompletionFrame {
+    pub invocation_id: Option<String>,
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct CloseFrame {
+    pub error: Option<String>,
+    #[serde(default)]
+    pub allow_reconnect: bool,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct ChatMessage {
+    pub text: Option<String>,
+    pub author: Option<String>,
+    pub message_type: Option<String>,
+    #[serde(default, deserialize_with = "lenient_vec")]
+    pub adaptive_cards: Vec<AdaptiveCard>,
+    pub source_attributions: Option<Vec<SourceAttribution>>,
+    pub suggested_responses: Option<Vec<SuggestedResponse>>,
+}
+
+impl ChatMessage {
+    /// Answer text written by the bot.
+    pub(crate) fn is_bot_answer(&self) -> bool {
+        self.author.as_deref() == Some("bot")
+            && self.text.as_deref().is_some_and(|text| !text.is_empty())
+    }
+
+    /// Request to generate image, its text is the image prompt.
+    pub(crate) fn is_generate_content_query(&self) -> bool {
+        self.message_type.as_deref() == Some("GenerateContentQuery")
+    }
+
+    /// Internal progress message, like "Searching the web for...".
+    pub(crate) fn is_progress(&self) -> bool {
+        matches!(
+            self.adaptive_cards
+                .last()
+                .and_then(|card| card.body.first()),
+            Some(CardElement::RichTextBlock { .. })
+        )
+    }
+}
+
+// Cards are only used to rebuild text with citations, so their elements are parsed
+// leniently: unexpected ones are skipped instead of failing the whole frame.
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct AdaptiveCard {
+    #[serde(default, deserialize_with = "lenient_vec")]
+    pub body: Vec<CardElement>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(tag = "type")]
+pub(crate) enum CardElement {
+    TextBlock {
+        #[serde(default, deserialize_with = "lenient_string")]
+        text: Option<String>,
+    },
+    RichTextBlock {
+        #[serde(default, deserialize_with = "lenient_vec")]
+        inlines: Vec<Inline>,
+    },
+    FactSet {
+        #[serde(default, deserialize_with = "lenient_vec")]
+        facts: Vec<Fact>,
+    },
+    Image {
+        #[serde(default, deserialize_with = "lenient_string")]
+        url: Option<String>,
+        #[serde(default, rename = "altText", deserialize_with = "lenient_string")]
+        alt_text: Option<String>,
+    },
+    Container {
+        #[serde(default, deserialize_with = "lenient_vec")]
+        items: Vec<CardElement>,
+    },
+    Column {
+        #[serde(default, deserialize_with = "lenient_vec")]
+        items: Vec<CardElement>,
+    },
+    ColumnSet {
+        #[serde(default, deserialize_with = "lenient_vec")]
+        columns: Vec<CardElement>,
+    },
+    #[serde(other)]
+    Unknown,
+}
+
+#[derive(Debug, Deserialize)]
+pub(crate) struct Inline {
+    #[serde(default, deserialize_with = "lenient_string")]
+    pub text: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub(crate) struct Fact {
+    #[serde(default, deserialize_with = "lenient_string")]
+    pub title: Option<String>,
+    #[serde(default, deserialize_with = "lenient_string")]
+    pub value: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct SourceAttribution {
+    pub provider_display_name: Option<String>,
+    pub see_more_url: Option<String>,
+    pub provider: Option<String>,
+    pub image_link: Option<String>,
+    #[serde(default, deserialize_with = "lenient_u32")]
+    pub image_width: Option<u32>,
+    #[serde(default, deserialize_with = "lenient_u32")]
+    pub image_height: Option<u32>,
+    pub image_favicon: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub(crate) struct SuggestedResponse {
+    pub text: Option<String>,
+}
+
+/// Number sent either as json number or as string.
+fn lenient_u32<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<u32>, D::Error> {
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Lenient {
+        Num(u64),
+        Str(String),
+        Other(serde::de::IgnoredAny),
+    }
+
+    Ok(match Option::<Lenient>::deserialize(deserializer)? {
+        Some(Lenient::Num(n)) => n.try_into().ok(),
+        Some(Lenient::Str(s)) => s.parse().ok(),
+        _ => None,
+    })
+}
+
+/// String, or `None` for null and values of any other type.
+fn lenient_string<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<String>, D::Error> {
+    Ok(match serde_json::Value::deserialize(deserializer)? {
+        serde_json::Value::String(s) => Some(s),
+        _ => None,
+    })
+}
+
+/// List without the elements that can't be parsed, empty for null and non-array values.
+fn lenient_vec<'de, D: Deserializer<'de>, T: DeserializeOwned>(
+    deserializer: D,
+) -> Result<Vec<T>, D::Error> {
+    Ok(match serde_json::Value::deserialize(deserializer)? {
+        serde_json::Value::Array(values) => values
+            .into_iter()
+            .filter_map(|value| serde_json::from_value(value).ok())
+            .collect(),
+        _ => Vec::new(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unexpected_card_elements_are_skipped() {
+        let frame = r#"{"type": 2, "item": {"messages": [{
+            "text": "Paris",
+            "author": "bot",
+            "adaptiveCards": [{"body": [
+                {"type": "TextBlock", "text": null},
+                {"type": "Image"},
+                {"type": "Container", "items": null},
+                {"type": "FactSet", "facts": [{"title": 1, "value": "x"}]},
+                "not an element",
+                {"type": "TextBlock", "text": "Paris"}
+            ]}, null]
+        }]}}"#;
+
+        let Frame::Result(result) = Frame::parse(frame).unwrap() else {
+            panic!("expected result frame");
+        };
+        let messages = result.item.messages.unwrap();
+        assert_eq!(messages[0].text.as_deref(), Some("Paris"));
+
+        let body = &messages[0].adaptive_cards[0].body;
+        assert_eq!(body.len(), 5);
+        assert!(matches!(&body[1], CardElement::Image { url: None, .. }));
+        assert!(matches!(&body[4], CardElement::TextBlock { text: Some(t) } if t == "Paris"));
+    }
+
+    #[test]
+    fn required_fields_stay_strict() {
+        let frame =
+            r#"{"type": 2, "item": {"throttling": {"maxNumUserMessagesInConversation": "x"}}}"#;
+        assert!(matches!(
+            Frame::parse(frame),
+            Err(SydneyError::ProtocolError {
+                frame: "result",
+                ..
+            })
+        ));
+    }
+}
@@ -0,0 +1,244 @@
+use crate::sydney::SydneyError;
+use base64::Engine;
+use reqwest::Url;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpStream;
+use tokio_socks::tcp::Socks5Stream;
+use tracing::warn;
+
+const ENV_VARS: [&str; 4] = ["HTTPS_PROXY", "https_proxy", "ALL_PROXY", "all_proxy"];
+
+/// Proxy used for both the create request and the ChatHub websocket.
+///
+/// Supports `http://` proxies (tunneled with `CONNECT`), `socks5://` (target resolved
+/// locally) and `socks5h://` (target resolved by the proxy), optionally with
+/// `user:password@` credentials.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Proxy {
+    url: Url,
+    no_proxy: Vec<String>,
+}
+
+impl Proxy {
+    pub fn new(url: &str) -> Result<Self, SydneyError> {
+        let url = Url::parse(url).map_err(|e| SydneyError::ProxyError(format!("{url}: {e}")))?;
+        if !matches!(url.scheme(), "http" | "socks5" | "socks5h") {
+            return Err(SydneyError::ProxyError(format!(
+                "Unsupported proxy scheme: {}",
+                url.scheme()
+            )));
+        }
+        if url.host_str().is_none() {
+            return Err(SydneyError::ProxyError(format!(
+                "Missing proxy host: {url}"
+            )));
+        }
+
+        Ok(Self {
+            url,
+            no_proxy: Vec::new(),
+        })
+    }
+
+    /// Proxy from `HTTPS_PROXY` or `ALL_PROXY` (or their lowercase forms), with hosts
+    /// from `NO_PROXY` excluded. Invalid values are ignored with a warning.
+    pub fn from_env() -> Option<Self> {
+        Self::from_vars(|name| std::env::var(name).ok())
+    }
+
+    fn from_vars(var: impl Fn(&str) -> Option<String>) -> Option<Self> {
+        let url = ENV_VARS
+            .iter()
+            .find_map(|name| var(name).filter(|v| !v.is_empty()))?;
+
+        match Self::new(&url) {
+            Ok(proxy) => {
+                let no_proxy = var("NO_PROXY")
+                    .or_else(|| var("no_proxy"))
+                    .unwrap_or_default();
+                Some(proxy.no_proxy(&no_proxy))
+            }
+            Err(e) => {
+                warn!("Ignoring proxy from environment: {e}");
+                None
+            }
+        }
+    }
+
+    /// Connect directly to these hosts, given as comma separated list in the `NO_PROXY`
+    /// format. Entries match the domain and its subdomains, `*` matches everything.
+    pub fn no_proxy(mut self, hosts: &str) -> Self {
+        self.no_proxy = hosts
+            .split(',')
+            .map(|h| h.trim().trim_start_matches('.').to_lowercase())
+            .filter(|h| !h.is_empty())
+            .collect();
+        self
+    }
+
+    pub fn url(&self) -> &str {
+        self.url.as_str()
+    }
+
+    pub(crate) fn bypass(&self, host: &str) -> bool {
+        let host = host
+            .trim_start_matches('[')
+            .trim_end_matches(']')
+            .to_lowercase();
+        self.no_proxy.iter().any(|entry| {
+            entry == "*"
+                || host == *entry
+                || host
+                    .strip_suffix(entry.as_str())
+                    .is_some_and(|rest| rest.ends_with('.'))
+        })
+    }
+
+    pub(crate) fn to_reqwest(&self) -> Result<reqwest::Proxy, SydneyError> {
+        let no_proxy = reqwest::NoProxy::from_string(&self.no_proxy.join(","));
+        Ok(reqwest::Proxy::all(self.url.clone())?.no_proxy(no_proxy))
+    }
+
+    /// Open tcp connection to `host:port` through this proxy.
+    pub(crate) async fn connect(&self, host: &str, port: u16) -> Result<TcpStream, SydneyError> {
+        let proxy_host = self.url.host_str().unwrap_or_default();
+        let proxy_port = self.url.port_or_known_default().unwrap_or(1080);
+        let credentials = self.credentials();
+
+        if self.url.scheme() == "http" {
+            let stream = TcpStream::connect((proxy_host, proxy_port)).await?;
+            return http_connect(stream, host, port, credentials).await;
+        }
+
+        let proxy = (proxy_host, proxy_port);
+        let stream = match (self.url.scheme(), &credentials) {
+            ("socks5", _) => {
+                let target = tokio::net::lookup_host((host, port))
+                    .await?
+                    .next()
+                    .ok_or_else(|| SydneyError::ProxyError(format!("Cannot resolve {host}")))?;
+                match &credentials {
+                    Some((user, pass)) => {
+                        Socks5Stream::connect_with_password(proxy, target, user, pass).await
+                    }
+                    None => Socks5Stream::connect(proxy, target).await,
+                }
+            }
+            (_, Some((user, pass))) => {
+                Socks5Stream::connect_with_password(proxy, (host, port), user, pass).await
+            }
+            (_, None) => Socks5Stream::connect(proxy, (host, port)).await,
+        }
+        .map_err(|e| SydneyError::ProxyError(e.to_string()))?;
+
+        Ok(stream.into_inner())
+    }
+
+    fn credentials(&self) -> Option<(String, String)> {
+        if self.url.username().is_empty() {
+            return None;
+        }
+
+        let decode = |s: &str| urlencoding::decode(s).map(|s| s.into_owned()).ok();
+        Some((
+            decode(self.url.username())?,
+            decode(self.url.password().unwrap_or_default())?,
+        ))
+    }
+}
+
+async fn http_connect(
+    mut stream: TcpStream,
+    host: &str,
+    port: u16,
+    credentials: Option<(String, String)>,
+) -> Result<TcpStream, SydneyError> {
+    let mut request = format!("CONNECT {host}:{port} HTTP/1.1\r\nHost: {host}:{port}\r\n");
+    if let Some((user, pass)) = credentials {
+        let auth = base64::engine::general_purpose::STANDARD.encode(format!("{user}:{pass}"));
+        request.push_str(&format!("Proxy-Authorization: Basic {auth}\r\n"));
+    }
+    request.push_str("\r\n");
+    stream.write_all(request.as_bytes()).await?;
+
+    // Read byte by byte, so nothing after the response head is consumed
+    let mut head = Vec::new();
+    while !head.ends_with(b"\r\n\r\n") {
+        if head.len() > 8192 {
+            return Err(SydneyError::ProxyError(
+                "Proxy response head too long".to_string(),
+            ));
+        }
+        head.push(stream.read_u8().await?);
+    }
+
+    let head = String::from_utf8_lossy(&head);
+    let status = head.lines().next().unwrap_or_default();
+    match status.split_whitespace().nth(1) {
+        Some("200") => Ok(stream),
+        _ => Err(SydneyError::ProxyError(format!(
+            "CONNECT to {host}:{port} failed: {status}"
+        ))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn from_vars(vars: &[(&str, &str)]) -> Option<Proxy> {
+        Proxy::from_vars(|name| {
+            vars.iter()
+                .find(|(n, _)| *n == name)
+                .map(|(_, v)| v.to_string())
+        })
+    }
+
+    #[test]
+    fn https_proxy_before_all_proxy() {
+        let proxy = from_vars(&[
+            ("ALL_PROXY", "socks5://all.local:1080"),
+            ("HTTPS_PROXY", "http://https.local:8080"),
+        ]);
+        assert_eq!(proxy.unwrap().url(), "http://https.local:8080/");
+    }
+
+    #[test]
+    fn uppercase_before_lowercase() {
+        let proxy = from_vars(&[
+            ("https_proxy", "http://lower.local:8080"),
+            ("HTTPS_PROXY", "http://upper.local:8080"),
+        ]);
+        assert_eq!(proxy.unwrap().url(), "http://upper.local:8080/");
+    }
+
+    #[test]
+    fn empty_values_are_skipped() {
+        let proxy = from_vars(&[
+            ("HTTPS_PROXY", ""),
+            ("all_proxy", "socks5h://all.local:1080"),
+        ]);
+        assert_eq!(proxy.unwrap().url(), "socks5h://all.local:1080");
+    }
+
+    #[test]
+    fn no_proxy_from_env() {
+        let proxy = from_vars(&[
+            ("HTTPS_PROXY", "http://proxy.local:8080"),
+            ("no_proxy", "lower.local"),
+            ("NO_PROXY", "localhost, .bing.com"),
+        ])
+        .unwrap();
+        assert!(proxy.bypass("localhost"));
+        assert!(proxy.bypass("www.bing.com"));
+        assert!(!proxy.bypass("lower.local"));
+        assert!(!proxy.bypass("example.com"));
+    }
+
+    #[test]
+    fn missing_or_invalid_env() {
+        assert_eq!(from_vars(&[]), None);
+        assert_eq!(from_vars(&[("NO_PROXY", "*")]), None);
+        assert_eq!(from_vars(&[("HTTPS_PROXY", "ftp://proxy.local:21")]), None);
+    }
+}
@@ -0,0 +1,30 @@
+use std::time::Duration;
+
+/// How to recover when the ChatHub websocket drops.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ReconnectPolicy {
+    /// Connection attempts after the first failed one, and also how many times
+    /// one interrupted prompt is sent again.
+    pub max_retries: u32,
+    /// Delay before the first retry, doubled with every next one.
+    pub delay: Duration,
+    /// Send the interrupted prompt again after reconnecting. Only done when
+    /// nothing of its answer was received yet, so the answer isn't duplicated.
+    pub retry_interrupted_ask: bool,
+}
+
+impl Default for ReconnectPolicy {
+    fn default() -> Self {
+        Self {
+            max_retries: 3,
+            delay: Duration::from_secs(1),
+            retry_interrupted_ask: true,
+        }
+    }
+}
+
+impl ReconnectPolicy {
+    pub(crate) fn retry_delay(&self, retry: u32) -> Duration {
+        self.delay.saturating_mul(2u32.saturating_pow(retry))
+    }
+}
@@ -0,0 +1,224 @@
+use anyhow::Result;
+use bing_ai_rust::{BingAIWs, Citation, ConversationStore, SydneyResponse, Tone};
+use futures_util::StreamExt;
+use std::io::Write;
+use tokio::io::{AsyncBufReadExt, BufReader};
+
+const HELP: &str = "Commands:
+  /tone <precise|creative|balanced>  change tone
+  /new                               start new conversation
+  /citations                         toggle citations
+  /suggestions                       toggle suggested responses
+  /help                              show this help
+  /quit                              exit
+Type number of a suggested response to send it, Ctrl-C stops the answer.";
+
+/// Interactive multi-turn chat in the terminal.
+pub struct Repl {
+    ai: BingAIWs,
+    store: ConversationStore,
+    citations: bool,
+    suggestions: bool,
+    last_suggestions: Vec<String>,
+}
+
+impl Repl {
+    pub fn new(ai: BingAIWs, store: ConversationStore) -> Self {
+        let mut repl = Self {
+            ai,
+            store,
+            citations: false,
+            suggestions: true,
+            last_suggestions: Vec::new(),
+        };
+        repl.apply_settings();
+        repl
+    }
+
+    pub async fn run(&mut self) -> Result<()> {
+        let mut lines = BufReader::new(tokio::io::stdin()).lines();
+        println!(
+            "Bing AI chat ({:?}). Type /help for commands.",
+            self.ai.tone()
+        );
+
+        loop {
+            print!("\n> ");
+            std::io::stdout().flush()?;
+
+            // Handling Ctrl-C replaces the default exit, so keep it at the prompt
+            let line = tokio::select! {
+                line = lines.next_line() => line?,
+                _ = tokio::signal::ctrl_c() => None,
+            };
+            let Some(line) = line else {
+                break;
+            };
+            let line = line.trim();
+            if line.is_empty() {
+                continue;
+            }
+
+            if let Some(command) = line.strip_prefix('/') {
+                if !self.command(command).await? {
+                    break;
+                }
+                continue;
+            }
+
+            let prompt = match line.parse::<usize>() {
+                Ok(n) if n >= 1 && n <= self.last_suggestions.len() => {
+                    let suggestion = self.last_suggestions[n - 1].clone();
+                    println!("> {suggestion}");
+                    suggestion
+                }
+                _ => line.to_string(),
+            };
+
+            if let Err(e) = self.ask(&prompt).await {
+                eprintln!("\nError: {e}");
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Handle slash command, returns `false` if repl should exit.
+    async fn command(&mut self, command: &str) -> Result<bool> {
+        let (name, arg) = command
+            .split_once(' ')
+            .map(|(name, arg)| (name, arg.trim()))
+            .unwrap_or((command, ""));
+
+        match name {
+            "quit" | "exit" | "q" => return Ok(false),
+            "help" => println!("{HELP}"),
+            "tone" => match arg.parse::<Tone>() {
+                Ok(tone) => {
+                    self.ai.set_tone(tone);
+                    println!("Tone set to {tone:?}");
+                }
+                Err(e) => println!("{e}"),
+            },
+            "new" => {
+                let tone = self.ai.tone();
+                self.ai = self.ai.start_new(tone).await?;
+                self.apply_settings();
+                self.store.save(&self.ai.export_state())?;
+                self.last_suggestions.clear();
+                println!("Started new conversation ({tone:?})");
+            }
+            "citations" => {
+                self.citations = !self.citations;
+                self.apply_settings();
+                println!("Citations: {}", on_off(self.citations));
+            }
+            "suggestions" => {
+                self.suggestions = !self.suggestions;
+                self.apply_settings();
+                println!("Suggestions: {}", on_off(self.suggestions));
+            }
+            _ => println!("Unknown command /{name}, type /help for commands"),
+        }
+
+        Ok(true)
+    }
+
+    async fn ask(&mut self, prompt: &str) -> Result<()> {
+        let mut stream = self.ai.ask(prompt).await?;
+        let cancel = stream.cancel_handle();
+        let ctrl_c = tokio::spawn(async move {
+            if tokio::signal::ctrl_c().await.is_ok() {
+                cancel.cancel();
+            }
+        });
+
+        let mut answer = String::new();
+        let mut printed = 0;
+        let mut sources: Vec<Citation> = Vec::new();
+        let mut suggestions = Vec::new();
+        let mut image_prompts = Vec::new();
+        let mut quota = None;
+
+        while let Some(msg) = stream.next().await {
+            let msg = match msg {
+                Ok(msg) => msg,
+                Err(e) => {
+                    ctrl_c.abort();
+                    return Err(e.into());
+                }
+            };
+
+            match msg {
+                SydneyResponse::StreamDelta(text) => answer.push_str(&text),
+                SydneyResponse::StreamRewrite { offset, text } => {
+                    answer.truncate(offset);
+                    answer.push_str(&text);
+                }
+                SydneyResponse::FinalText(text) => answer = text,
+                SydneyResponse::Sources(citations) => sources = citations,
+                SydneyResponse::SuggestedResponses(responses) => suggestions = responses,
+                SydneyResponse::ImageGenerationRequest { prompt } => image_prompts.push(prompt),
+                SydneyResponse::Quota { used, max } => quota = Some((used, max)),
+                SydneyResponse::Rollover { .. } => {
+                    println!("(Message limit reached, continuing in a new conversation)")
+                }
+                SydneyResponse::StreamText(_) => {}
+            }
+
+            // Already printed text can't be changed, so only print what's after it
+            if answer.len() > printed && answer.is_char_boundary(printed) {
+                print!("{}", &answer[printed..]);
+                std::io::stdout().flush()?;
+                printed = answer.len();
+            }
+        }
+        println!();
+
+        ctrl_c.abort();
+        drop(stream);
+        self.store.save(&self.ai.export_state())?;
+
+        if self.citations && !sources.is_empty() {
+            println!();
+            for citation in &sources {
+                println!("[{}] {} - {}", citation.index, citation.title, citation.url);
+            }
+        }
+
+        for prompt in image_prompts {
+            println!("\nGenerating images: {prompt}");
+            for url in self.ai.image_creator().create(&prompt).await? {
+                println!("  {url}");
+            }
+        }
+
+        self.last_suggestions = suggestions;
+        if !self.last_suggestions.is_empty() {
+            println!();
+            for (i, suggestion) in self.last_suggestions.iter().enumerate() {
+                println!("  {}. {suggestion}", i + 1);
+            }
+        }
+
+        if let Some((used, max)) = quota {
+            println!("\n({used}/{max} messages)");
+        }
+
+        Ok(())
+    }
+
+    fn apply_settings(&mut self) {
+        self.ai.set_deltas(true);
+        self.ai.set_citations(self.citations);
+        self.ai.set_suggestions(self.suggestions);
+    }
+}
+
+fn on_off(value: bool) -> &'static str {
+    if value {
+        "on"
+    } else {
+        "off"
+    }
+}
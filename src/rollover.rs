@@ -0,0 +1,45 @@
+use std::collections::VecDeque;
+
+/// Continue in a new conversation when the current one reaches its message limit,
+/// instead of failing with [`SydneyError::MaxMessagesCountLimitReached`](crate::SydneyError::MaxMessagesCountLimitReached).
+#[derive(Debug, Clone, PartialEq)]
+pub struct RolloverPolicy {
+    /// Recent turns carried over to the new conversation as context.
+    pub turns: usize,
+    /// Limit of the carried over context, oldest turns are dropped first.
+    pub max_context_chars: usize,
+}
+
+impl Default for RolloverPolicy {
+    fn default() -> Self {
+        Self {
+            turns: 5,
+            max_context_chars: 4000,
+        }
+    }
+}
+
+impl RolloverPolicy {
+    /// Recent turns in the chat format Bing uses for previous messages.
+    pub(crate) fn context(&self, turns: &VecDeque<(String, String)>) -> Option<String> {
+        let mut blocks = Vec::new();
+        let mut len = 0;
+
+        for (prompt, answer) in turns.iter().rev().take(self.turns) {
+            let block =
+                format!("[user](#message)\n{prompt}\n\n[assistant](#message)\n{answer}\n\n");
+            len += block.chars().count();
+            if len > self.max_context_chars {
+                break;
+            }
+            blocks.push(block);
+        }
+
+        if blocks.is_empty() {
+            return None;
+        }
+
+        blocks.reverse();
+        Some(blocks.concat())
+    }
+}
@@ -0,0 +1,156 @@
+use crate::sydney::SydneyError;
+use crate::types::ConversationState;
+use serde::de::DeserializeOwned;
+use std::fs::File;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+/// Directory with saved [`ConversationState`]s, one json file per conversation.
+pub struct ConversationStore {
+    dir: PathBuf,
+}
+
+impl ConversationStore {
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+
+    /// Store in the user data directory (e.g. `~/.local/share/bing-ai/conversations`).
+    #[cfg(feature = "dirs")]
+    pub fn open_default() -> Result<Self, SydneyError> {
+        Ok(Self::new(data_dir()?.join("conversations")))
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Save state, replacing previously saved state of the same conversation.
+    /// The file is only readable by the user, signatures grant access to the conversation.
+    pub fn save(&self, state: &ConversationState) -> Result<(), SydneyError> {
+        let json = serde_json::to_string_pretty(state)?;
+        write_private(&self.path(&state.conversation_id), |file| {
+            Ok(file.write_all(json.as_bytes())?)
+        })
+    }
+
+    pub fn load(&self, conversation_id: &str) -> Result<Option<ConversationState>, SydneyError> {
+        load_json(&self.path(conversation_id))
+    }
+
+    /// Most recently saved conversation.
+    pub fn latest(&self) -> Result<Option<ConversationState>, SydneyError> {
+        Ok(self.list()?.into_iter().next())
+    }
+
+    /// All saved conversations, most recently saved first.
+    pub fn list(&self) -> Result<Vec<ConversationState>, SydneyError> {
+        load_json_files(&self.dir)
+    }
+
+    /// Remove saved conversation, returns `false` if it wasn't saved.
+    pub fn remove(&self, conversation_id: &str) -> Result<bool, SydneyError> {
+        remove_file(&self.path(conversation_id))
+    }
+
+    fn path(&self, conversation_id: &str) -> PathBuf {
+        json_path(&self.dir, conversation_id)
+    }
+}
+
+/// Data directory of the crate (e.g. `~/.local/share/bing-ai`).
+#[cfg(feature = "dirs")]
+pub(crate) fn data_dir() -> Result<PathBuf, SydneyError> {
+    Ok(dirs::data_dir()
+        .ok_or_else(|| anyhow::anyhow!("Cannot find user data directory"))?
+        .join("bing-ai"))
+}
+
+/// Write file only readable by the user (0600 on unix). It's written to a temporary
+/// file moved over the old one, so it's never left half written.
+pub(crate) fn write_private(
+    path: &Path,
+    write: impl FnOnce(&mut File) -> Result<(), SydneyError>,
+) -> Result<(), SydneyError> {
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)?;
+    }
+
+    let mut tmp_name = path.as_os_str().to_owned();
+    tmp_name.push(".tmp");
+    let tmp = PathBuf::from(tmp_name);
+
+    let mut options = std::fs::OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    #[cfg(unix)]
+    std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+
+    let mut file = options.open(&tmp)?;
+    // Mode only applies to new files, the temporary one may be left from before
+    #[cfg(unix)]
+    std::fs::set_permissions(&tmp, std::os::unix::fs::PermissionsExt::from_mode(0o600))?;
+    write(&mut file)?;
+    file.flush()?;
+    drop(file);
+
+    std::fs::rename(&tmp, path)?;
+    Ok(())
+}
+
+/// Conversation ids contain chars like `|`, which aren't allowed in file names everywhere.
+pub(crate) fn file_stem(conversation_id: &str) -> String {
+    conversation_id
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '-' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect()
+}
+
+/// File of the conversation in the directory.
+pub(crate) fn json_path(dir: &Path, conversation_id: &str) -> PathBuf {
+    dir.join(format!("{}.json", file_stem(conversation_id)))
+}
+
+/// Parsed json file, `None` if it doesn't exist.
+pub(crate) fn load_json<T: DeserializeOwned>(path: &Path) -> Result<Option<T>, SydneyError> {
+    if !path.exists() {
+        return Ok(None);
+    }
+
+    Ok(Some(serde_json::from_str(&std::fs::read_to_string(path)?)?))
+}
+
+/// All `.json` files in the directory, most recently modified first.
+pub(crate) fn load_json_files<T: DeserializeOwned>(dir: &Path) -> Result<Vec<T>, SydneyError> {
+    if !dir.exists() {
+        return Ok(Vec::new());
+    }
+
+    let mut files = Vec::new();
+    for entry in std::fs::read_dir(dir)? {
+        let entry = entry?;
+        let path = entry.path();
+        if path.extension().is_some_and(|ext| ext == "json") {
+            let value: T = serde_json::from_str(&std::fs::read_to_string(&path)?)?;
+            files.push((entry.metadata()?.modified()?, value));
+        }
+    }
+
+    files.sort_by_key(|(modified, _)| std::cmp::Reverse(*modified));
+    Ok(files.into_iter().map(|(_, value)| value).collect())
+}
+
+/// Remove file, returns `false` if it doesn't exist.
+pub(crate) fn remove_file(path: &Path) -> Result<bool, SydneyError> {
+    if !path.exists() {
+        return Ok(false);
+    }
+
+    std::fs::remove_file(path)?;
+    Ok(true)
+}
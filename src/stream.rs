@@ -0,0 +1,78 @@
+use crate::cancel::CancelHandle;
+use crate::sydney::{BingAIWs, SydneyError, SydneyResponse};
+use anyhow::anyhow;
+use futures_util::{stream, Stream, StreamExt};
+use std::collections::VecDeque;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+type Inner<'a> = Pin<Box<dyn Stream<Item = Result<SydneyResponse, SydneyError>> + Send + 'a>>;
+
+/// Stream of responses to a single prompt, returned by [`BingAIWs::ask`].
+///
+/// Ends after the final message of the answer. Dropping it mid-answer is safe,
+/// next [`BingAIWs::ask`] starts with a clean websocket.
+pub struct ResponseStream<'a> {
+    inner: Inner<'a>,
+    cancel: CancelHandle,
+}
+
+impl<'a> ResponseStream<'a> {
+    pub(crate) fn new(ai: &'a mut BingAIWs) -> Self {
+        let cancel = ai.cancel_handle();
+        let inner = stream::unfold(
+            (ai, VecDeque::new(), false),
+            |(ai, mut queue, failed)| async move {
+                loop {
+                    if let Some(msg) = queue.pop_front() {
+                        return Some((Ok(msg), (ai, queue, failed)));
+                    }
+
+                    if failed {
+                        return None;
+                    }
+
+                    match ai.get_next_msgs().await {
+                        Ok(msgs) => queue.extend(msgs),
+                        Err(SydneyError::EndOfResponse) => return None,
+                        Err(e) => return Some((Err(e), (ai, queue, true))),
+                    }
+                }
+            },
+        );
+
+        Self {
+            inner: Box::pin(inner),
+            cancel,
+        }
+    }
+
+    /// Stop generating the answer, the stream ends on its next poll.
+    pub fn cancel(&self) {
+        self.cancel.cancel();
+    }
+
+    /// Handle to stop generating the answer from another task.
+    pub fn cancel_handle(&self) -> CancelHandle {
+        self.cancel.clone()
+    }
+
+    /// Consume the stream and return the final text of the answer.
+    pub async fn final_text(mut self) -> Result<String, SydneyError> {
+        while let Some(msg) = self.next().await {
+            if let SydneyResponse::FinalText(text) = msg? {
+                return Ok(text);
+            }
+        }
+
+        Err(SydneyError::OtherError(anyhow!("No final message found!")))
+    }
+}
+
+impl Stream for ResponseStream<'_> {
+    type Item = Result<SydneyResponse, SydneyError>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.inner.as_mut().poll_next(cx)
+    }
+}
@@ -1,30 +1,129 @@
-use crate::types::Tone;
-use anyhow::{anyhow, Result};
+use crate::adaptive_card::cards_text;
+use crate::builder::BingAIWsBuilder;
+use crate::cancel::CancelHandle;
+use crate::citation::Citation;
+use crate::cookie_jar::CookieJar;
+use crate::delta::DeltaTracker;
+use crate::event::{ErrorInfo, ErrorKind};
+use crate::image::{ImageInput, ImageUrls};
+use crate::image_creator::ImageCreator;
+use crate::protocol::Frame;
+use crate::proxy::Proxy;
+use crate::reconnect::ReconnectPolicy;
+use crate::rollover::RolloverPolicy;
+use crate::stream::ResponseStream;
+use crate::throttle::ThrottleInfo;
+use crate::timeout::{with_timeout, TimeoutKind, Timeouts};
+use crate::transcript::{TranscriptStore, Turn};
+use crate::types::{ConversationState, Tone};
+use anyhow::anyhow;
 use futures_util::{future, pin_mut, StreamExt};
+use serde::{Deserialize, Serialize};
 use serde_json::json;
+use std::collections::VecDeque;
+use std::sync::Arc;
 use thiserror::Error;
-use tokio_tungstenite::{connect_async, tungstenite::Message};
-use tracing::{debug, error, info, trace};
+use tokio_tungstenite::tungstenite::client::IntoClientRequest;
+use tokio_tungstenite::tungstenite::{http, Message};
+use tokio_tungstenite::{client_async_tls, connect_async};
+use tracing::{debug, trace, warn};
 
 const USER_AGENT: &str = "Mozilla/5.0 (X11; Linux x86_64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/120.0.0.0 Safari/537.36";
 const CREATE_URL: &str = "https://www.bing.com/turing/conversation/create";
 const WS_URL: &str = "wss://sydney.bing.com/sydney/ChatHub";
+const IMAGE_UPLOAD_URL: &str = "https://www.bing.com/images/kblob";
+const IMAGE_CREATOR_URL: &str = "https://www.bing.com/images/create";
 const BUNDLE_VERSION: &str = "1.1586.1";
 const DELIMETER: &str = "\x1E";
 
+/// Endpoints and client identity used to talk to Bing.
+#[derive(Debug, Clone)]
+pub(crate) struct ClientConfig {
+    pub create_url: String,
+    pub ws_url: String,
+    pub bundle_version: String,
+    pub user_agent: String,
+    pub headers: Vec<(String, String)>,
+    pub proxy: Option<Proxy>,
+    pub timeouts: Timeouts,
+    pub image_upload_url: String,
+    pub image_creator_url: String,
+    pub cookie_jar: Option<Arc<CookieJar>>,
+}
+
+impl Default for ClientConfig {
+    fn default() -> Self {
+        Self {
+            create_url: CREATE_URL.to_string(),
+            ws_url: WS_URL.to_string(),
+            bundle_version: BUNDLE_VERSION.to_string(),
+            user_agent: USER_AGENT.to_string(),
+            headers: Vec::new(),
+            proxy: Proxy::from_env(),
+            timeouts: Timeouts::default(),
+            image_upload_url: IMAGE_UPLOAD_URL.to_string(),
+            image_creator_url: IMAGE_CREATOR_URL.to_string(),
+            cookie_jar: None,
+        }
+    }
+}
+
+/// Errors returned by [`BingAIWs`].
 #[derive(Error, Debug)]
 pub enum SydneyError {
     #[error("WebSocket not connected!")]
     WebSocketNotConnected,
 
+    #[error("Create conversation request failed: {0}")]
+    CreateConversationFailed(String),
+
+    #[error("Http error: {0}")]
+    HttpError(#[from] reqwest::Error),
+
+    #[error("WebSocket error: {0}")]
+    WebSocketError(Box<tokio_tungstenite::tungstenite::Error>),
+
     #[error("Json parsing error: {0}")]
     JsonParsingError(#[from] serde_json::Error),
 
+    #[error("Cannot parse {frame} frame: {source}")]
+    ProtocolError {
+        frame: &'static str,
+        source: serde_json::Error,
+    },
+
+    #[error("Io error: {0}")]
+    IoError(#[from] std::io::Error),
+
+    #[error("WebSocket disconnected!")]
+    Disconnected,
+
+    #[error("Server error: {0}")]
+    ServerError(String),
+
+    #[error("Timed out waiting for {0}")]
+    Timeout(TimeoutKind),
+
+    #[error("Image upload failed: {0}")]
+    ImageUploadFailed(String),
+
+    #[error("Image generation failed: {0}")]
+    ImageGenerationFailed(String),
+
+    #[error("Proxy error: {0}")]
+    ProxyError(String),
+
+    #[error("Missing _U auth cookie")]
+    MissingAuthCookie,
+
     #[error("Max messages count limit reached!")]
     MaxMessagesCountLimitReached,
 
-    #[error("Thorttling error!")]
-    ThrottlingError,
+    #[error("Captcha required: {0}")]
+    CaptchaRequired(ThrottleInfo),
+
+    #[error("Throttled: {0}")]
+    Throttled(ThrottleInfo),
 
     #[error("End of response")]
     EndOfResponse,
@@ -33,16 +132,89 @@ pub enum SydneyError {
     OtherError(#[from] anyhow::Error),
 }
 
-#[derive(Debug)]
+impl SydneyError {
+    /// Kind of the error, stable identifier used in its json form.
+    pub fn kind(&self) -> ErrorKind {
+        match self {
+            Self::WebSocketNotConnected => ErrorKind::WebSocketNotConnected,
+            Self::CreateConversationFailed(_) => ErrorKind::CreateConversationFailed,
+            Self::HttpError(_) => ErrorKind::HttpError,
+            Self::WebSocketError(_) => ErrorKind::WebSocketError,
+            Self::JsonParsingError(_) => ErrorKind::JsonParsingError,
+            Self::ProtocolError { .. } => ErrorKind::ProtocolError,
+            Self::IoError(_) => ErrorKind::IoError,
+            Self::Disconnected => ErrorKind::Disconnected,
+            Self::ServerError(_) => ErrorKind::ServerError,
+            Self::Timeout(_) => ErrorKind::Timeout,
+            Self::ImageUploadFailed(_) => ErrorKind::ImageUploadFailed,
+            Self::ImageGenerationFailed(_) => ErrorKind::ImageGenerationFailed,
+            Self::ProxyError(_) => ErrorKind::ProxyError,
+            Self::MissingAuthCookie => ErrorKind::MissingAuthCookie,
+            Self::MaxMessagesCountLimitReached => ErrorKind::MaxMessagesCountLimitReached,
+            Self::CaptchaRequired(_) => ErrorKind::CaptchaRequired,
+            Self::Throttled(_) => ErrorKind::Throttled,
+            Self::EndOfResponse => ErrorKind::EndOfResponse,
+            Self::OtherError(_) => ErrorKind::OtherError,
+        }
+    }
+}
+
+impl From<tokio_tungstenite::tungstenite::Error> for SydneyError {
+    fn from(e: tokio_tungstenite::tungstenite::Error) -> Self {
+        Self::WebSocketError(Box::new(e))
+    }
+}
+
+impl Serialize for SydneyError {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        ErrorInfo::from(self).serialize(serializer)
+    }
+}
+
+/// Single event parsed from the ChatHub stream.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", content = "data", rename_all = "camelCase")]
 pub enum SydneyResponse {
+    /// Complete answer, sent once at the end of the response.
     FinalText(String),
+
+    /// Whole answer generated so far (not only the new part).
     StreamText(String),
+
+    /// Text appended to the answer since the previous stream message
+    /// (only with [`BingAIWs::set_deltas`]).
+    StreamDelta(String),
+
+    /// Server rewrote already streamed text (e.g. inserted citations). Answer text
+    /// starting at byte `offset` should be replaced with `text`
+    /// (only with [`BingAIWs::set_deltas`]).
+    StreamRewrite { offset: usize, text: String },
+
+    /// Suggested follow-up prompts (only with [`BingAIWs::set_suggestions`]).
     SuggestedResponses(Vec<String>),
-    Sources(Vec<String>),
+
+    /// Sources used in the answer, referenced in the text with `[^N^]` markers.
+    Sources(Vec<Citation>),
+
+    /// Bing decided to draw an image (Creative tone). Generate it with
+    /// [`ImageCreator::create`](crate::ImageCreator::create).
+    ImageGenerationRequest { prompt: String },
+
+    /// User messages sent in this conversation and the most it allows, e.g. 7 of 30.
+    /// Sent with every answer.
+    Quota { used: u32, max: u32 },
+
+    /// Conversation reached its message limit and continues in a new one
+    /// (only with [`BingAIWs::set_rollover_policy`]).
+    Rollover {
+        previous_conversation_id: String,
+        conversation_id: String,
+    },
 }
 
-#[allow(dead_code)]
+/// Bing AI (Sydney) conversation connected over the ChatHub websocket.
 pub struct BingAIWs {
+    config: ClientConfig,
     close_ws_after: bool,
     citations: bool,
     suggestions: bool,
@@ -52,6 +224,24 @@ pub struct BingAIWs {
     invocation_id: i64,
     end_of_response: bool,
     tone: Tone,
+    delta: Option<DeltaTracker>,
+    last_prompt: String,
+    last_ask: Option<serde_json::Value>,
+    answer_started: bool,
+    frame_received: bool,
+    resends: u32,
+    image_prompts: Vec<String>,
+    quota: Option<(u32, u32)>,
+    last_image: Option<ImageUrls>,
+    rollover: Option<RolloverPolicy>,
+    rollover_pending: bool,
+    rolled_over: bool,
+    recent_turns: VecDeque<(String, String)>,
+    context: Option<String>,
+    queued: Vec<SydneyResponse>,
+    cancel: CancelHandle,
+    reconnect: Option<ReconnectPolicy>,
+    transcripts: Option<TranscriptStore>,
 
     client_id: String,
     conversation_id: String,
@@ -64,46 +254,86 @@ pub struct BingAIWs {
     )>,
 }
 
-#[allow(dead_code)]
 impl BingAIWs {
-    pub async fn new(tone: Tone) -> Result<Self> {
+    /// Create new conversation without cookies.
+    pub async fn new(tone: Tone) -> Result<Self, SydneyError> {
         Self::new_conversation(tone, None).await
     }
 
-    pub async fn new_with_cookies(tone: Tone, cookies: &str) -> Result<Self> {
+    /// Create new conversation authenticated with raw `Cookie:` header value.
+    pub async fn new_with_cookies(tone: Tone, cookies: &str) -> Result<Self, SydneyError> {
         Self::new_conversation(tone, Some(cookies.to_string())).await
     }
 
-    pub async fn new_conversation(tone: Tone, cookies: Option<String>) -> Result<Self> {
-        let mut headers = reqwest::header::HeaderMap::new();
+    /// Create new conversation, optionally authenticated with raw `Cookie:` header value.
+    pub async fn new_conversation(
+        tone: Tone,
+        cookies: Option<String>,
+    ) -> Result<Self, SydneyError> {
+        Self::create(ClientConfig::default(), tone, cookies).await
+    }
 
-        if let Some(cookies) = cookies {
-            headers.insert(
-                reqwest::header::COOKIE,
-                reqwest::header::HeaderValue::from_str(&cookies)?,
-            );
-        }
+    /// Configure endpoints, user agent or extra headers before connecting.
+    pub fn builder() -> BingAIWsBuilder {
+        BingAIWsBuilder::new()
+    }
 
-        let client = reqwest::ClientBuilder::new()
-            .user_agent(USER_AGENT)
-            .default_headers(headers)
-            .cookie_store(true)
-            .build()?;
+    pub(crate) async fn create(
+        config: ClientConfig,
+        tone: Tone,
+        cookies: Option<String>,
+    ) -> Result<Self, SydneyError> {
+        let client = build_client(&config, cookies)?;
+        Self::create_with_client(config, client, tone).await
+    }
 
-        let res = client
-            .get(format!("{CREATE_URL}?bundleVersion={BUNDLE_VERSION}"))
-            .send()
-            .await?;
+    /// Start a new conversation with the same endpoints, headers, cookies and settings
+    /// (including reconnect, rollover and transcript recording).
+    pub async fn start_new(&self, tone: Tone) -> Result<Self, SydneyError> {
+        let mut next =
+            Self::create_with_client(self.config.clone(), self.client.clone(), tone).await?;
+
+        next.close_ws_after = self.close_ws_after;
+        next.citations = self.citations;
+        next.suggestions = self.suggestions;
+        next.set_deltas(self.delta.is_some());
+        next.reconnect = self.reconnect.clone();
+        next.rollover = self.rollover.clone();
+        next.transcripts = self.transcripts.clone();
+
+        Ok(next)
+    }
 
-        let res_headers = res.headers().clone();
+    async fn create_with_client(
+        config: ClientConfig,
+        client: reqwest::Client,
+        tone: Tone,
+    ) -> Result<Self, SydneyError> {
+        let url = format!(
+            "{}?bundleVersion={}",
+            config.create_url, config.bundle_version
+        );
+        let (res_headers, res_json) =
+            with_timeout(config.timeouts.create, TimeoutKind::Create, async {
+                let res = client.get(url).send().await?;
+                let res_headers = res.headers().clone();
+                let res_json: crate::types::CreateRoot = res.json().await?;
+                Ok((res_headers, res_json))
+            })
+            .await?;
 
-        let res_json: crate::types::CreateRoot = res.json().await?;
-        if let Some(result) = res_json.result {
-            if result.value.unwrap_or("NO".to_string()) != "Success" {
-                return Err(anyhow!("Create request failed!"));
+        match res_json.result {
+            Some(result) if result.value.as_deref() == Some("Success") => {}
+            Some(result) => {
+                return Err(SydneyError::CreateConversationFailed(
+                    result.value.unwrap_or("NO".to_string()),
+                ))
+            }
+            None => {
+                return Err(SydneyError::CreateConversationFailed(
+                    "No result".to_string(),
+                ))
             }
-        } else {
-            return Err(anyhow!("Create request failed!"));
         }
 
         let client_id = res_json
@@ -117,13 +347,15 @@ impl BingAIWs {
         let encrypted_conversation_signature = res_headers
             .get("X-Sydney-EncryptedConversationSignature")
             .ok_or_else(|| anyhow!("Cannot get encrypted conversation signature header!"))?
-            .to_str()?
+            .to_str()
+            .map_err(anyhow::Error::from)?
             .to_string();
 
         let conversation_signature = res_headers
             .get("X-Sydney-ConversationSignature")
             .ok_or_else(|| anyhow!("Cannot get conversation signature header!"))?
-            .to_str()?
+            .to_str()
+            .map_err(anyhow::Error::from)?
             .to_string();
 
         debug!("Client id: {client_id}");
@@ -131,23 +363,84 @@ impl BingAIWs {
         debug!("Conversation signature: {conversation_signature}");
         debug!("Encrypted conversation signature: {encrypted_conversation_signature}");
 
-        Ok(Self {
+        Ok(Self::from_state(
+            config,
+            client,
+            ConversationState {
+                client_id,
+                conversation_id,
+                conversation_signature,
+                encrypted_conversation_signature,
+                invocation_id: 0,
+                tone,
+            },
+        ))
+    }
+
+    /// Reconnect to existing conversation exported with [`BingAIWs::export_state`].
+    /// Websocket is connected on the next [`BingAIWs::ask`].
+    pub fn resume(state: ConversationState, cookies: Option<String>) -> Result<Self, SydneyError> {
+        Self::resume_with_config(ClientConfig::default(), state, cookies)
+    }
+
+    pub(crate) fn resume_with_config(
+        config: ClientConfig,
+        state: ConversationState,
+        cookies: Option<String>,
+    ) -> Result<Self, SydneyError> {
+        let client = build_client(&config, cookies)?;
+        Ok(Self::from_state(config, client, state))
+    }
+
+    /// Everything needed to resume this conversation later with [`BingAIWs::resume`].
+    pub fn export_state(&self) -> ConversationState {
+        ConversationState {
+            client_id: self.client_id.clone(),
+            conversation_id: self.conversation_id.clone(),
+            conversation_signature: self.conversation_signature.clone(),
+            encrypted_conversation_signature: self.encrypted_conversation_signature.clone(),
+            invocation_id: self.invocation_id,
+            tone: self.tone,
+        }
+    }
+
+    fn from_state(config: ClientConfig, client: reqwest::Client, state: ConversationState) -> Self {
+        Self {
+            config,
             close_ws_after: false,
             citations: false,
             suggestions: false,
 
             client,
 
-            invocation_id: 0,
+            invocation_id: state.invocation_id,
             end_of_response: true,
-            tone,
-
-            client_id,
-            conversation_id,
-            conversation_signature,
-            encrypted_conversation_signature,
+            tone: state.tone,
+            delta: None,
+            last_prompt: String::new(),
+            last_ask: None,
+            answer_started: false,
+            frame_received: false,
+            resends: 0,
+            image_prompts: Vec::new(),
+            quota: None,
+            last_image: None,
+            rollover: None,
+            rollover_pending: false,
+            rolled_over: false,
+            recent_turns: VecDeque::new(),
+            context: None,
+            queued: Vec::new(),
+            cancel: CancelHandle::new(),
+            reconnect: None,
+            transcripts: None,
+
+            client_id: state.client_id,
+            conversation_id: state.conversation_id,
+            conversation_signature: state.conversation_signature,
+            encrypted_conversation_signature: state.encrypted_conversation_signature,
             ws: None,
-        })
+        }
     }
 
     /// Set whether to close ws after asking a question and receiving a response.
@@ -156,8 +449,7 @@ impl BingAIWs {
     }
 
     /// Set whether to include citations in the response. (Like url's etc.)
-    /// Its not working well on larger responses, because orginal creator of sydney.py
-    /// haven't implemented it well. (I'll fix it in the future)
+    /// Text is then rebuilt from the adaptive cards of the message, with `[^1^]` markers.
     pub fn set_citations(&mut self, citations: bool) {
         self.citations = citations;
     }
@@ -167,48 +459,262 @@ impl BingAIWs {
         self.suggestions = suggestions;
     }
 
-    pub async fn ask(&mut self, prompt: &str) -> Result<()> {
+    /// Set whether to stream only newly appended text ([`SydneyResponse::StreamDelta`])
+    /// instead of the whole answer generated so far ([`SydneyResponse::StreamText`]).
+    pub fn set_deltas(&mut self, deltas: bool) {
+        self.delta = deltas.then(DeltaTracker::default);
+    }
+
+    /// Set how to recover from dropped websocket. Without policy the answer fails with
+    /// [`SydneyError::Disconnected`] and next [`BingAIWs::ask`] connects again.
+    pub fn set_reconnect_policy(&mut self, policy: Option<ReconnectPolicy>) {
+        self.reconnect = policy;
+    }
+
+    /// Set store where every prompt and its final answer is recorded.
+    pub fn set_transcript_store(&mut self, store: Option<TranscriptStore>) {
+        self.transcripts = store;
+    }
+
+    /// Set tone used for the next prompts.
+    pub fn set_tone(&mut self, tone: Tone) {
+        self.tone = tone;
+    }
+
+    /// Tone used for the next prompts.
+    pub fn tone(&self) -> Tone {
+        self.tone
+    }
+
+    /// Continue in a new conversation when this one reaches its message limit,
+    /// reported with [`SydneyResponse::Rollover`].
+    pub fn set_rollover_policy(&mut self, policy: Option<RolloverPolicy>) {
+        self.rollover = policy;
+    }
+
+    /// Messages sent in this conversation and the most it allows, as of the last answer.
+    pub fn quota(&self) -> Option<(u32, u32)> {
+        self.quota
+    }
+
+    /// Id of the conversation created by Bing.
+    pub fn conversation_id(&self) -> &str {
+        &self.conversation_id
+    }
+
+    /// Send prompt to the conversation and return stream of the answer.
+    ///
+    /// If the previous answer wasn't read to the end (e.g. its stream was dropped),
+    /// websocket is reconnected so leftover messages don't leak into the new answer.
+    pub async fn ask(&mut self, prompt: &str) -> Result<ResponseStream<'_>, SydneyError> {
+        self.send_ask(prompt, None).await
+    }
+
+    /// Send prompt with an image, registered with the Bing image blob endpoint first.
+    /// Files and bytes are uploaded, urls are fetched by Bing.
+    pub async fn ask_with_image(
+        &mut self,
+        prompt: &str,
+        image: impl Into<ImageInput>,
+    ) -> Result<ResponseStream<'_>, SydneyError> {
+        let image = crate::image::upload(
+            &self.client,
+            &self.config.image_upload_url,
+            image.into(),
+            &self.conversation_id,
+            self.tone,
+        )
+        .await?;
+        debug!("Image uploaded: {}", image.image_url);
+
+        self.send_ask(prompt, Some(image)).await
+    }
+
+    async fn send_ask(
+        &mut self,
+        prompt: &str,
+        image: Option<ImageUrls>,
+    ) -> Result<ResponseStream<'_>, SydneyError> {
+        if !self.end_of_response {
+            debug!("Previous response not finished, reconnecting ws");
+            self.close_ws();
+            self.end_of_response = true;
+        }
+
+        // Stream of every answer gets its own handle, kept when the prompt is re-sent
+        self.cancel = CancelHandle::new();
+        self.rolled_over = false;
+
+        let mut queued = Vec::new();
+        if self.rollover_pending {
+            queued.push(self.roll_over().await?);
+        }
+
+        self.send_prompt(prompt, image).await?;
+        self.queued = queued;
+
+        Ok(ResponseStream::new(self))
+    }
+
+    async fn send_prompt(
+        &mut self,
+        prompt: &str,
+        image: Option<ImageUrls>,
+    ) -> Result<(), SydneyError> {
+        // Bing closes idle sockets, so it may have dropped since the last answer
+        if self.ws.as_ref().is_some_and(|(tx, _)| tx.is_closed()) {
+            debug!("WebSocket dropped since the last answer, reconnecting");
+            self.close_ws();
+        }
         if self.ws.is_none() {
-            self.connect_ws().await?;
+            self.connect_ws_with_retry().await?;
         }
 
-        let ask_json = crate::json::ask_json(
+        let mut ask_json = crate::json::ask_json(
             prompt,
             self.invocation_id,
             &self.tone,
             &self.conversation_signature,
             &self.client_id,
             &self.conversation_id,
+            image.as_ref(),
         );
+        if let Some(context) = &self.context {
+            crate::json::add_context(&mut ask_json, context);
+        }
 
         let tx = &self
             .ws
             .as_ref()
             .ok_or_else(|| SydneyError::WebSocketNotConnected)?
             .0;
-        send_ws_delim(tx, ask_json)?;
+        if send_ws_delim(tx, ask_json.clone()).is_err() {
+            self.close_ws();
+            return Err(SydneyError::Disconnected);
+        }
+        self.cancel
+            .set_target(Some(tx.clone()), self.invocation_id.to_string());
 
         self.invocation_id += 1;
         self.end_of_response = false;
+        self.context = None;
+        self.last_prompt = prompt.to_string();
+        self.last_image = image;
+        self.last_ask = Some(ask_json);
+        self.answer_started = false;
+        self.frame_received = false;
+        self.resends = 0;
+        self.image_prompts.clear();
+        if let Some(delta) = &mut self.delta {
+            delta.reset();
+        }
+
         Ok(())
     }
 
+    /// Create new conversation, seeded with recent turns of this one.
+    async fn roll_over(&mut self) -> Result<SydneyResponse, SydneyError> {
+        let next =
+            Self::create_with_client(self.config.clone(), self.client.clone(), self.tone).await?;
+
+        self.close_ws();
+        let previous_conversation_id =
+            std::mem::replace(&mut self.conversation_id, next.conversation_id);
+        self.client_id = next.client_id;
+        self.conversation_signature = next.conversation_signature;
+        self.encrypted_conversation_signature = next.encrypted_conversation_signature;
+        self.invocation_id = 0;
+        self.end_of_response = true;
+        self.rollover_pending = false;
+        self.rolled_over = true;
+        self.quota = None;
+        self.context = self
+            .rollover
+            .as_ref()
+            .and_then(|policy| policy.context(&self.recent_turns));
+
+        debug!(
+            "Rolled over from {previous_conversation_id} to {}",
+            self.conversation_id
+        );
+        Ok(SydneyResponse::Rollover {
+            previous_conversation_id,
+            conversation_id: self.conversation_id.clone(),
+        })
+    }
+
+    /// Image generator sharing cookies, proxy and headers with this conversation.
+    pub fn image_creator(&self) -> ImageCreator {
+        ImageCreator::new(self.client.clone(), self.config.image_creator_url.clone())
+    }
+
+    /// Stop generating the current answer. The conversation stays usable for the next
+    /// [`BingAIWs::ask`].
+    pub fn cancel(&mut self) {
+        if self.end_of_response {
+            return;
+        }
+
+        self.cancel_handle().cancel();
+        self.close_ws();
+        self.end_of_response = true;
+    }
+
+    /// Handle cancelling the current answer, see [`ResponseStream::cancel_handle`].
+    pub(crate) fn cancel_handle(&self) -> CancelHandle {
+        self.cancel.clone()
+    }
+
+    /// Stream of the remaining messages of the current answer.
+    pub fn responses(&mut self) -> ResponseStream<'_> {
+        ResponseStream::new(self)
+    }
+
+    /// Wait for the next websocket message and parse it into responses.
+    /// Returns [`SydneyError::EndOfResponse`] once the whole answer has been received.
     pub async fn get_next_msgs(&mut self) -> Result<Vec<SydneyResponse>, SydneyError> {
         if self.end_of_response {
             return Err(SydneyError::EndOfResponse);
         }
 
-        let rx = &mut self
+        if !self.queued.is_empty() {
+            return Ok(std::mem::take(&mut self.queued));
+        }
+
+        let (tx, rx) = self
             .ws
             .as_mut()
-            .ok_or_else(|| SydneyError::WebSocketNotConnected)?
-            .1;
+            .ok_or_else(|| SydneyError::WebSocketNotConnected)?;
+
+        let (limit, kind) = match self.frame_received {
+            true => (self.config.timeouts.idle, TimeoutKind::Idle),
+            false => (
+                self.config.timeouts.first_message,
+                TimeoutKind::FirstMessage,
+            ),
+        };
+        let cancel = self.cancel.notify();
+        let msg = tokio::select! {
+            msg = with_timeout(limit, kind, async { Ok(rx.recv().await) }) => msg,
+            _ = cancel.notified() => Err(SydneyError::EndOfResponse),
+        };
+        let msg = match msg {
+            Ok(msg) => msg,
+            Err(e) => {
+                debug!("Answer ended early: {e}");
+                // Late frames of this answer would mix with the next one
+                self.close_ws();
+                self.end_of_response = true;
+                return Err(e);
+            }
+        };
+        self.frame_received = true;
 
         let mut responses = Vec::new();
-        let msg = rx.recv().await;
         let msg_str = match msg {
             Some(Message::Text(str)) => str,
-            _ => return Err(SydneyError::OtherError(anyhow!("Msg not text"))),
+            Some(Message::Close(_)) | None => return self.handle_disconnect().await,
+            Some(_) => return Ok(responses),
         };
 
         for ws_str in msg_str.split(DELIMETER) {
@@ -216,223 +722,332 @@ impl BingAIWs {
                 continue;
             }
 
-            let ws_json: serde_json::Value = serde_json::from_str(ws_str)?;
-            //debug!("{ws_json}");
-            let typ = ws_json
-                .get("type")
-                .ok_or_else(|| anyhow!("Json type field not found!"))?;
+            match Frame::parse(ws_str)? {
+                Frame::Update(update) => {
+                    let Some(message) = update
+                        .arguments
+                        .into_iter()
+                        .next()
+                        .and_then(|args| args.messages)
+                        .and_then(|messages| messages.into_iter().next())
+                    else {
+                        continue;
+                    };
 
-            if typ == 1 {
-                let messages = if let Some(messages) = ws_json["arguments"][0].get("messages") {
-                    messages
-                } else {
-                    continue;
-                };
+                    // Skip "Searching in web for..." msg
+                    if message.is_progress() {
+                        continue;
+                    }
 
-                // Skip "Searching in web for..." msg
-                let adaptive_cards = messages[0].get("adaptiveCards");
-                if let Some(adaptive_cards) = adaptive_cards {
-                    if adaptive_cards[0]["body"][0].get("inlines").is_some() {
+                    if message.is_generate_content_query() {
+                        responses.extend(image_generation_request(
+                            &mut self.image_prompts,
+                            message.text,
+                        ));
                         continue;
                     }
 
-                    if self.citations {
-                        if let Some(text) = adaptive_cards[0]["body"][0].get("text") {
-                            responses.push(SydneyResponse::StreamText(
-                                text.as_str()
-                                    .ok_or_else(|| anyhow!("Text isnt a string"))?
-                                    .to_string(),
-                            ));
-                        } else {
-                            let text = &adaptive_cards[0]["body"][1]["text"];
-                            responses.push(SydneyResponse::StreamText(
-                                text.as_str()
-                                    .ok_or_else(|| anyhow!("Text isnt a string"))?
-                                    .to_string(),
-                            ));
-                        }
+                    let text = if self.citations {
+                        cards_text(&message.adaptive_cards)
+                    } else {
+                        message.text
+                    };
+
+                    if let Some(text) = text {
+                        responses.extend(stream_response(&mut self.delta, text));
+                        self.answer_started = true;
                     }
                 }
+                Frame::Result(result) => {
+                    let item = result.item;
+                    if let Some(throttling) = item.throttling {
+                        let messages_count = throttling.num_user_messages_in_conversation;
+                        let max_messages = throttling.max_num_user_messages_in_conversation;
+                        self.quota = Some((messages_count, max_messages));
+
+                        if messages_count >= max_messages {
+                            debug!(
+                                "Max messages count limit reached! ({messages_count}/{max_messages})"
+                            );
+
+                            let answered = self.answer_started
+                                || item.messages.iter().flatten().any(|m| m.is_bot_answer());
+                            match (&self.rollover, answered) {
+                                (None, _) => return Err(SydneyError::MaxMessagesCountLimitReached),
+                                // Answer is fine, next prompt goes to a new conversation
+                                (Some(_), true) => self.rollover_pending = true,
+                                // New conversation rejected the prompt too
+                                (Some(_), false) if self.rolled_over => {
+                                    return Err(SydneyError::MaxMessagesCountLimitReached)
+                                }
+                                (Some(_), false) => {
+                                    let rollover = self.roll_over().await?;
+                                    let prompt = self.last_prompt.clone();
+                                    self.send_prompt(&prompt, self.last_image.clone()).await?;
+                                    return Ok(vec![rollover]);
+                                }
+                            }
+                        }
+                    }
+
+                    if let Some(result) = item.result {
+                        match result.value.as_deref() {
+                            Some("Throttled") => {
+                                debug!("Throttled result (type 2 msg)");
+                                let info = ThrottleInfo::from_result(result, false);
+                                return Err(SydneyError::Throttled(info));
+                            }
+                            Some("CaptchaChallenge") => {
+                                debug!("Captcha! (type 2 msg)");
+                                let info = ThrottleInfo::from_result(result, true);
+                                return Err(SydneyError::CaptchaRequired(info));
+                            }
+                            _ if item.messages.is_none() => {
+                                let value = result.value.unwrap_or("NOT FOUND".to_string());
+                                let message = result.message.unwrap_or_default();
+                                debug!("Result without messages: {value}");
+                                return Err(SydneyError::ServerError(format!(
+                                    "{value}: {message}"
+                                )));
+                            }
+                            _ => {}
+                        }
+                    }
+
+                    let messages = item
+                        .messages
+                        .ok_or_else(|| anyhow!("No messages in the result"))?;
 
-                if !self.citations {
-                    if let Some(text) = messages[0].get("text") {
-                        responses.push(SydneyResponse::StreamText(
-                            text.as_str()
-                                .ok_or_else(|| anyhow!("Text isnt a string"))?
-                                .to_string(),
+                    if let Some((used, max)) = self.quota {
+                        responses.push(SydneyResponse::Quota { used, max });
+                    }
+
+                    let (image_requests, mut messages): (Vec<_>, Vec<_>) = messages
+                        .into_iter()
+                        .partition(|message| message.is_generate_content_query());
+                    for request in image_requests {
+                        responses.extend(image_generation_request(
+                            &mut self.image_prompts,
+                            request.text,
                         ));
                     }
-                }
-            } else if typ == 2 {
-                if let Some(throttling) = ws_json["item"].get("throttling") {
-                    let messages_count = throttling
-                        .get("numUserMessagesInConversation")
-                        .unwrap_or(&serde_json::Value::Number(serde_json::Number::from(0)))
-                        .as_i64()
-                        .unwrap_or(0);
-
-                    let max_messages = throttling["maxNumUserMessagesInConversation"]
-                        .as_i64()
-                        .ok_or_else(|| anyhow!("Cannot read max user msgs"))?;
-
-                    if messages_count == max_messages {
-                        debug!(
-                            "Max messages count limit reached! ({messages_count}/{max_messages})"
-                        );
 
-                        return Err(SydneyError::MaxMessagesCountLimitReached);
+                    if messages.last().is_some_and(|message| message.is_progress()) {
+                        messages.pop();
                     }
-                }
 
-                let messages = if let Some(messages) = ws_json["item"].get("messages") {
-                    messages
-                } else {
-                    let result = ws_json["item"]["result"]["value"]
-                        .as_str()
-                        .unwrap_or("NOT FOUND");
+                    let message = messages
+                        .pop()
+                        .ok_or_else(|| anyhow!("No messages in the result"))?;
 
-                    debug!("Result: {result}");
+                    if let Some(sources) = &message.source_attributions {
+                        let citations = Citation::from_source_attributions(sources);
+                        responses.push(SydneyResponse::Sources(citations));
+                    }
 
-                    match result {
-                        "Throttled" => debug!("Throttled result (type 2 msg)"),
-                        "CaptchaChallenge" => debug!("Captcha! (type 2 msg)"),
-                        _ => {}
+                    let suggestions: Option<Vec<String>> =
+                        message.suggested_responses.map(|suggested_responses| {
+                            suggested_responses
+                                .into_iter()
+                                .filter_map(|sr| sr.text)
+                                .collect()
+                        });
+
+                    if self.suggestions {
+                        if let Some(suggestions) = &suggestions {
+                            responses.push(SydneyResponse::SuggestedResponses(suggestions.clone()));
+                        }
                     }
 
-                    return Err(SydneyError::ThrottlingError);
-                };
-
-                let messages = messages
-                    .as_array()
-                    .ok_or_else(|| anyhow!("Messages not found/not an array"))?;
-                let mut i = messages.len() - 1;
-
-                if let Some(adaptive_cards) = messages
-                    .last()
-                    .ok_or_else(|| anyhow!("Cannot get last msg"))?
-                    .get("adaptiveCards")
-                {
-                    let adaptive_cards = adaptive_cards
-                        .as_array()
-                        .ok_or_else(|| anyhow!("Adaptive cards not found/not an array"))?;
-                    if adaptive_cards
-                        .last()
-                        .ok_or_else(|| anyhow!("Cannot get last adaptive card"))?["body"][0]
-                        .get("inlines")
-                        .is_some()
-                    {
-                        i = messages.len() - 2;
+                    let text = if self.citations {
+                        cards_text(&message.adaptive_cards).or(message.text)
+                    } else {
+                        message.text
+                    };
+
+                    if let Some(text) = text {
+                        if let Some(policy) = &self.rollover {
+                            self.recent_turns
+                                .push_back((self.last_prompt.clone(), text.clone()));
+                            while self.recent_turns.len() > policy.turns {
+                                self.recent_turns.pop_front();
+                            }
+                        }
+                        responses.push(SydneyResponse::FinalText(text));
                     }
-                }
 
-                let message = messages
-                    .get(i)
-                    .ok_or_else(|| anyhow!("Message with that idx doesnt exists (impossible)"))?;
-
-                if let Some(sources) = message.get("sourceAttributions") {
-                    let sources: Vec<&str> = sources
-                        .as_array()
-                        .ok_or_else(|| anyhow!("Sources not an array"))?
-                        .iter()
-                        .filter(|s| s["provider"].as_str() == Some("search_web"))
-                        .filter_map(|s| s["seeMoreUrl"].as_str())
-                        .collect();
-
-                    responses.push(SydneyResponse::Sources(
-                        sources.iter().map(|s| s.to_string()).collect(),
-                    ));
-                }
+                    if let Some(transcripts) = &self.transcripts {
+                        let turn = Turn::new(
+                            &self.last_prompt,
+                            &responses,
+                            suggestions.unwrap_or_default(),
+                        );
+                        // History is a side feature, so the answer is returned anyway
+                        if let Err(e) = transcripts.append(&self.conversation_id, turn) {
+                            warn!("Cannot record transcript: {e}");
+                        }
+                    }
 
-                if self.suggestions {
-                    if let Some(suggested_responses) = message.get("suggestedResponses") {
-                        let suggested_responses: Vec<&str> = suggested_responses
-                            .as_array()
-                            .ok_or_else(|| anyhow!("Suggested responses not an array"))?
-                            .iter()
-                            .filter_map(|sr| sr["text"].as_str())
-                            .collect();
-
-                        responses.push(SydneyResponse::SuggestedResponses(
-                            suggested_responses.iter().map(|s| s.to_string()).collect(),
-                        ));
+                    if self.close_ws_after {
+                        self.close_ws();
+                    } else {
+                        _ = clear_recv_chan(rx).await;
                     }
+
+                    self.end_of_response = true;
+                    break;
                 }
+                Frame::Completion(completion) => {
+                    // Completion of the invocation, may come without type 2 msg on errors
+                    let invocation_id = completion.invocation_id.unwrap_or_default();
+                    if invocation_id != (self.invocation_id - 1).to_string() {
+                        trace!("Completion of old invocation {invocation_id}");
+                        continue;
+                    }
 
-                if self.citations {
-                    // thats bad - body of adaptive cards is array and message is splitted into
-                    // that array, so it should iterate over that array
-                    if let Some(text) = message["adaptiveCards"][0]["body"][0].get("text") {
-                        responses.push(SydneyResponse::FinalText(
-                            text.as_str()
-                                .ok_or_else(|| anyhow!("Text isnt a string"))?
-                                .to_string(),
-                        ));
-                    } else {
-                        let text = &message["adaptiveCards"][0]["body"][1]["text"];
-                        responses.push(SydneyResponse::FinalText(
-                            text.as_str()
-                                .ok_or_else(|| anyhow!("Text isnt a string"))?
-                                .to_string(),
-                        ));
+                    self.end_of_response = true;
+                    if let Some(error) = completion.error {
+                        debug!("Completion error: {error}");
+                        return Err(SydneyError::ServerError(error));
                     }
-                } else if let Some(text) = message.get("text") {
-                    responses.push(SydneyResponse::FinalText(
-                        text.as_str()
-                            .ok_or_else(|| anyhow!("Text isnt a string"))?
-                            .to_string(),
-                    ));
+
+                    break;
                 }
+                Frame::Ping => send_ws_delim(tx, json!({ "type": 6 }))?,
+                Frame::Close(close) => {
+                    debug!("Close msg (allow reconnect: {})", close.allow_reconnect);
+
+                    if let Some(error) = close.error {
+                        self.close_ws();
+                        self.end_of_response = true;
+                        return Err(SydneyError::ServerError(error));
+                    }
 
-                if self.close_ws_after {
-                    if let Some(ref mut ws) = self.ws {
-                        ws.0.close_channel();
-                        ws.1.close();
+                    if close.allow_reconnect {
+                        return self.handle_disconnect().await;
                     }
-                    self.ws = None;
-                } else {
-                    _ = clear_recv_chan(rx).await;
+
+                    self.close_ws();
+                    self.end_of_response = true;
+                    return Err(SydneyError::Disconnected);
+                }
+                Frame::Unknown(kind) => trace!("Unknown msg type: {kind}"),
+            }
+        }
+
+        Ok(responses)
+    }
+
+    /// Read messages until the end of the response and return the final text.
+    pub async fn get_final_response(&mut self) -> Result<String, SydneyError> {
+        self.responses().final_text().await
+    }
+
+    fn close_ws(&mut self) {
+        if let Some(ref mut ws) = self.ws {
+            ws.0.close_channel();
+            ws.1.close();
+        }
+        self.ws = None;
+    }
+
+    /// Reconnect after the websocket dropped and, if it's safe, send the interrupted
+    /// prompt again.
+    async fn handle_disconnect(&mut self) -> Result<Vec<SydneyResponse>, SydneyError> {
+        debug!("WebSocket disconnected");
+        self.close_ws();
+
+        let retry_ask = self.reconnect.as_ref().is_some_and(|policy| {
+            policy.retry_interrupted_ask && self.resends < policy.max_retries
+        }) && !self.answer_started;
+
+        if let (true, Some(ask_json)) = (retry_ask, self.last_ask.clone()) {
+            let resent = match self.connect_ws_with_retry().await {
+                Ok(()) => {
+                    let tx = &self
+                        .ws
+                        .as_ref()
+                        .ok_or_else(|| SydneyError::WebSocketNotConnected)?
+                        .0;
+                    send_ws_delim(tx, ask_json).map_err(SydneyError::from)
                 }
+                Err(e) => Err(e),
+            };
 
+            if let Err(e) = resent {
                 self.end_of_response = true;
-                break;
+                return Err(e);
             }
+
+            self.frame_received = false;
+            self.resends += 1;
+            debug!("Interrupted prompt sent again ({})", self.resends);
+            return Ok(Vec::new());
         }
 
-        Ok(responses)
+        self.end_of_response = true;
+        Err(SydneyError::Disconnected)
     }
 
-    pub async fn get_final_response(&mut self) -> Result<String> {
+    /// Connect websocket, retrying according to the reconnect policy.
+    async fn connect_ws_with_retry(&mut self) -> Result<(), SydneyError> {
+        let mut retry = 0;
         loop {
-            let res = self.get_next_msgs().await;
-            match res {
-                Err(SydneyError::EndOfResponse) => {
-                    debug!("End of response");
-                    break;
-                }
+            let limit = self.config.timeouts.handshake;
+            match with_timeout(limit, TimeoutKind::Handshake, self.connect_ws()).await {
+                Ok(()) => return Ok(()),
                 Err(e) => {
-                    error!("Error: {}", e);
-                    break;
-                }
-                Ok(msgs) => {
-                    for msg in msgs {
-                        match msg {
-                            SydneyResponse::FinalText(text) => {
-                                return Ok(text);
-                            }
-                            _ => {}
-                        }
+                    let Some(policy) = &self.reconnect else {
+                        return Err(e);
+                    };
+                    if retry >= policy.max_retries {
+                        return Err(e);
                     }
+
+                    let delay = policy.retry_delay(retry);
+                    warn!("WebSocket connection failed ({e}), retrying in {delay:?}");
+                    tokio::time::sleep(delay).await;
+                    retry += 1;
                 }
             }
         }
-
-        Err(anyhow!("No final message found!"))
     }
 
-    async fn connect_ws(&mut self) -> Result<()> {
+    async fn connect_ws(&mut self) -> Result<(), SydneyError> {
         let url_encoded_ecs = urlencoding::encode(&self.encrypted_conversation_signature);
-        let (ws_stream, _) =
-            connect_async(&format!("{WS_URL}?sec_access_token={url_encoded_ecs}")).await?;
+        let mut request = format!("{}?sec_access_token={url_encoded_ecs}", self.config.ws_url)
+            .into_client_request()?;
+
+        let headers = request.headers_mut();
+        headers.insert(
+            http::header::USER_AGENT,
+            http::HeaderValue::from_str(&self.config.user_agent).map_err(anyhow::Error::from)?,
+        );
+        for (name, value) in &self.config.headers {
+            headers.insert(
+                http::HeaderName::from_bytes(name.as_bytes()).map_err(anyhow::Error::from)?,
+                http::HeaderValue::from_str(value).map_err(anyhow::Error::from)?,
+            );
+        }
+
+        let (ws_stream, _) = match &self.config.proxy {
+            Some(proxy) => {
+                let uri = request.uri();
+                let host = uri.host().unwrap_or_default().to_string();
+                let port = uri.port_u16().unwrap_or(match uri.scheme_str() {
+                    Some("ws") => 80,
+                    _ => 443,
+                });
+
+                if proxy.bypass(&host) {
+                    connect_async(request).await?
+                } else {
+                    let stream = proxy.connect(&host, port).await?;
+                    client_async_tls(request, stream).await?
+                }
+            }
+            None => connect_async(request).await?,
+        };
 
         let (tx_write, rx_write) = futures_channel::mpsc::unbounded();
         let (tx_read, mut rx_read) = tokio::sync::mpsc::unbounded_channel();
@@ -442,9 +1057,12 @@ impl BingAIWs {
             let write_fut = rx_write.map(Ok).forward(write);
             let read_fut = {
                 read.for_each(|msg| async {
-                    if let Ok(msg) = msg {
-                        trace!("WS msg: {msg:?}");
-                        _ = tx_read.send(msg);
+                    match msg {
+                        Ok(msg) => {
+                            trace!("WS msg: {msg:?}");
+                            _ = tx_read.send(msg);
+                        }
+                        Err(e) => debug!("WS read error: {e}"),
                     }
                 })
             };
@@ -460,24 +1078,86 @@ impl BingAIWs {
                 "version": 1
             }),
         )?;
-        rx_read.recv().await;
+        rx_read.recv().await.ok_or(SydneyError::Disconnected)?;
 
         self.ws = Some((tx_write, rx_read));
         Ok(())
     }
 }
 
-fn send_ws_delim(
+pub(crate) fn build_client(
+    config: &ClientConfig,
+    cookies: Option<String>,
+) -> Result<reqwest::Client, SydneyError> {
+    let mut headers = reqwest::header::HeaderMap::new();
+
+    // Cookie header would stop the jar from sending its cookies, so they're stored in it
+    match (&config.cookie_jar, cookies) {
+        (Some(jar), Some(cookies)) => jar.insert_header(&cookies, &config.create_url)?,
+        (None, Some(cookies)) => {
+            headers.insert(
+                reqwest::header::COOKIE,
+                reqwest::header::HeaderValue::from_str(&cookies).map_err(anyhow::Error::from)?,
+            );
+        }
+        (_, None) => {}
+    }
+
+    for (name, value) in &config.headers {
+        headers.insert(
+            reqwest::header::HeaderName::from_bytes(name.as_bytes())
+                .map_err(anyhow::Error::from)?,
+            reqwest::header::HeaderValue::from_str(value).map_err(anyhow::Error::from)?,
+        );
+    }
+
+    let builder = reqwest::ClientBuilder::new()
+        .user_agent(&config.user_agent)
+        .default_headers(headers);
+
+    let builder = match &config.cookie_jar {
+        Some(jar) => builder.cookie_provider(jar.clone()),
+        None => builder.cookie_store(true),
+    };
+
+    let builder = match &config.proxy {
+        Some(proxy) => builder.proxy(proxy.to_reqwest()?),
+        None => builder.no_proxy(),
+    };
+
+    Ok(builder.build()?)
+}
+
+fn stream_response(delta: &mut Option<DeltaTracker>, text: String) -> Option<SydneyResponse> {
+    match delta {
+        Some(delta) => delta.update(text),
+        None => Some(SydneyResponse::StreamText(text)),
+    }
+}
+
+/// Image generation request, unless it was already reported in this answer.
+fn image_generation_request(
+    reported: &mut Vec<String>,
+    prompt: Option<String>,
+) -> Option<SydneyResponse> {
+    let prompt = prompt.filter(|p| !p.is_empty() && !reported.contains(p))?;
+    reported.push(prompt.clone());
+    Some(SydneyResponse::ImageGenerationRequest { prompt })
+}
+
+pub(crate) fn send_ws_delim(
     tx: &futures_channel::mpsc::UnboundedSender<Message>,
     val: serde_json::Value,
-) -> Result<()> {
-    let json_str = format!("{}{DELIMETER}", val.to_string());
+) -> anyhow::Result<()> {
+    let json_str = format!("{val}{DELIMETER}");
     tx.unbounded_send(Message::Text(json_str))?;
 
     Ok(())
 }
 
-async fn clear_recv_chan(rx: &mut tokio::sync::mpsc::UnboundedReceiver<Message>) -> Result<()> {
+async fn clear_recv_chan(
+    rx: &mut tokio::sync::mpsc::UnboundedReceiver<Message>,
+) -> anyhow::Result<()> {
     loop {
         rx.try_recv()?;
     }
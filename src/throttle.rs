@@ -0,0 +1,47 @@
+use crate::protocol::ResultStatus;
+use std::fmt;
+use std::time::Duration;
+
+/// Page where the captcha can be solved in the browser.
+const CAPTCHA_URL: &str = "https://www.bing.com/turing/captcha/challenge";
+
+/// Details of a throttled or captcha result, from the type 2 `result` object.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ThrottleInfo {
+    /// Result message sent by the server.
+    pub message: String,
+    /// Page where the challenge has to be solved before asking again.
+    pub challenge_url: Option<String>,
+    /// How long to wait before asking again, if the server sent it.
+    pub retry_after: Option<Duration>,
+}
+
+impl ThrottleInfo {
+    pub(crate) fn from_result(result: ResultStatus, captcha: bool) -> Self {
+        let message = result.message.unwrap_or_default();
+        let challenge_url = message
+            .split_whitespace()
+            .find(|word| word.starts_with("https://"))
+            .map(|url| url.trim_end_matches(['.', ',', ')']).to_string())
+            .or_else(|| captcha.then(|| CAPTCHA_URL.to_string()));
+
+        Self {
+            message,
+            challenge_url,
+            retry_after: result.retry_after.map(|s| Duration::from_secs(s.into())),
+        }
+    }
+}
+
+impl fmt::Display for ThrottleInfo {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.message)?;
+        if let Some(url) = &self.challenge_url {
+            write!(f, " ({url})")?;
+        }
+        if let Some(retry_after) = self.retry_after {
+            write!(f, " retry after {}s", retry_after.as_secs())?;
+        }
+        Ok(())
+    }
+}
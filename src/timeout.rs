@@ -0,0 +1,66 @@
+use crate::sydney::SydneyError;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::future::Future;
+use std::time::Duration;
+
+/// Limits for waiting on Bing, `None` waits forever.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Timeouts {
+    /// Conversation create request.
+    pub create: Option<Duration>,
+    /// Websocket connection, including the protocol handshake.
+    pub handshake: Option<Duration>,
+    /// From sending a prompt to the first frame of its answer.
+    pub first_message: Option<Duration>,
+    /// Between two frames of an answer.
+    pub idle: Option<Duration>,
+}
+
+impl Default for Timeouts {
+    fn default() -> Self {
+        Self {
+            create: Some(Duration::from_secs(30)),
+            handshake: Some(Duration::from_secs(30)),
+            first_message: Some(Duration::from_secs(60)),
+            idle: Some(Duration::from_secs(60)),
+        }
+    }
+}
+
+/// Which of the [`Timeouts`] elapsed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum TimeoutKind {
+    Create,
+    Handshake,
+    FirstMessage,
+    Idle,
+    /// Images of [`ImageCreator`](crate::ImageCreator), with its own limit.
+    ImageGeneration,
+}
+
+impl fmt::Display for TimeoutKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Create => "create request",
+            Self::Handshake => "websocket handshake",
+            Self::FirstMessage => "first message",
+            Self::Idle => "next message",
+            Self::ImageGeneration => "generated images",
+        })
+    }
+}
+
+pub(crate) async fn with_timeout<T>(
+    limit: Option<Duration>,
+    kind: TimeoutKind,
+    fut: impl Future<Output = Result<T, SydneyError>>,
+) -> Result<T, SydneyError> {
+    match limit {
+        Some(limit) => tokio::time::timeout(limit, fut)
+            .await
+            .map_err(|_| SydneyError::Timeout(kind))?,
+        None => fut.await,
+    }
+}
@@ -0,0 +1,142 @@
+use crate::citation::Citation;
+use crate::store::{json_path, load_json, load_json_files, remove_file, write_private};
+use crate::sydney::{SydneyError, SydneyResponse};
+use serde::{Deserialize, Serialize};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Recorded prompts and answers of a single conversation.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Transcript {
+    pub conversation_id: String,
+    pub turns: Vec<Turn>,
+}
+
+/// Prompt with its final answer.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Turn {
+    /// Unix timestamp (seconds) of the answer.
+    pub timestamp: u64,
+    pub prompt: String,
+    pub answer: String,
+    pub sources: Vec<Citation>,
+    pub suggestions: Vec<String>,
+}
+
+impl Turn {
+    pub(crate) fn new(
+        prompt: &str,
+        responses: &[SydneyResponse],
+        suggestions: Vec<String>,
+    ) -> Self {
+        let mut turn = Self {
+            timestamp: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|d| d.as_secs())
+                .unwrap_or_default(),
+            prompt: prompt.to_string(),
+            answer: String::new(),
+            sources: Vec::new(),
+            suggestions,
+        };
+
+        for response in responses {
+            match response {
+                SydneyResponse::FinalText(text) => turn.answer = text.clone(),
+                SydneyResponse::Sources(sources) => turn.sources = sources.clone(),
+                _ => {}
+            }
+        }
+
+        turn
+    }
+}
+
+/// Turn matching the search query.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SearchHit {
+    pub conversation_id: String,
+    /// Index of the turn in [`Transcript::turns`].
+    pub turn_index: usize,
+    pub turn: Turn,
+}
+
+/// Directory with [`Transcript`]s, one json file per conversation.
+#[derive(Debug, Clone)]
+pub struct TranscriptStore {
+    dir: PathBuf,
+}
+
+impl TranscriptStore {
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+
+    /// Store in the user data directory (e.g. `~/.local/share/bing-ai/transcripts`).
+    #[cfg(feature = "dirs")]
+    pub fn open_default() -> Result<Self, SydneyError> {
+        Ok(Self::new(crate::store::data_dir()?.join("transcripts")))
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Append turn to the transcript of the conversation. Like saved conversations, the
+    /// file is only readable by the user.
+    pub fn append(&self, conversation_id: &str, turn: Turn) -> Result<(), SydneyError> {
+        let mut transcript = self.load(conversation_id)?.unwrap_or_else(|| Transcript {
+            conversation_id: conversation_id.to_string(),
+            turns: Vec::new(),
+        });
+        transcript.turns.push(turn);
+
+        let json = serde_json::to_string_pretty(&transcript)?;
+        write_private(&self.path(conversation_id), |file| {
+            Ok(file.write_all(json.as_bytes())?)
+        })
+    }
+
+    pub fn load(&self, conversation_id: &str) -> Result<Option<Transcript>, SydneyError> {
+        load_json(&self.path(conversation_id))
+    }
+
+    /// All transcripts, most recently updated first.
+    pub fn list(&self) -> Result<Vec<Transcript>, SydneyError> {
+        load_json_files(&self.dir)
+    }
+
+    /// Case insensitive search in prompts and answers of all transcripts.
+    pub fn search(&self, query: &str) -> Result<Vec<SearchHit>, SydneyError> {
+        let query = query.to_lowercase();
+        let mut hits = Vec::new();
+
+        for transcript in self.list()? {
+            for (turn_index, turn) in transcript.turns.into_iter().enumerate() {
+                if turn.prompt.to_lowercase().contains(&query)
+                    || turn.answer.to_lowercase().contains(&query)
+                {
+                    hits.push(SearchHit {
+                        conversation_id: transcript.conversation_id.clone(),
+                        turn_index,
+                        turn,
+                    });
+                }
+            }
+        }
+
+        Ok(hits)
+    }
+
+    /// Remove transcript, returns `false` if it doesn't exist.
+    pub fn remove(&self, conversation_id: &str) -> Result<bool, SydneyError> {
+        remove_file(&self.path(conversation_id))
+    }
+
+    fn path(&self, conversation_id: &str) -> PathBuf {
+        json_path(&self.dir, conversation_id)
+    }
+}
@@ -1,5 +1,8 @@
+use crate::sydney::SydneyError;
+use anyhow::anyhow;
 use serde::{Deserialize, Serialize};
-use serde_json::{json, Value};
+use serde_json::Value;
+use std::str::FromStr;
 
 #[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -16,6 +19,20 @@ pub struct CreateResult {
     pub message: Option<Value>,
 }
 
+/// Identity of a conversation, enough to reconnect to it from another process.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConversationState {
+    pub conversation_id: String,
+    pub client_id: String,
+    pub conversation_signature: String,
+    pub encrypted_conversation_signature: String,
+    pub invocation_id: i64,
+    pub tone: Tone,
+}
+
+/// Conversation style of the chat.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum Tone {
     Precise,
     Creative,
@@ -40,3 +57,15 @@ impl Tone {
     }
 }
 
+impl FromStr for Tone {
+    type Err = SydneyError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "precise" => Ok(Self::Precise),
+            "creative" => Ok(Self::Creative),
+            "balanced" => Ok(Self::Balanced),
+            _ => Err(SydneyError::OtherError(anyhow!("Unknown tone: {s}"))),
+        }
+    }
+}
@@ -0,0 +1,1269 @@
+use bing_ai_rust::mock::{MockAction, MockServer};
+use bing_ai_rust::{
+    BrowserCookies, ConversationStore, CookieJar, ImageInput, Proxy, ReconnectPolicy,
+    RolloverPolicy, SydneyError, SydneyResponse, TimeoutKind, Timeouts, Tone, TranscriptStore,
+};
+use futures_util::StreamExt;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+
+const PROMPT: &str = "What is the capital of France?";
+
+async fn collect(
+    ai: &mut bing_ai_rust::BingAIWs,
+    prompt: &str,
+) -> Result<Vec<SydneyResponse>, SydneyError> {
+    let mut stream = ai.ask(prompt).await?;
+    let mut responses = Vec::new();
+    while let Some(response) = stream.next().await {
+        responses.push(response?);
+    }
+    Ok(responses)
+}
+
+#[tokio::test]
+async fn streams_deltas_and_final_text() {
+    let server = MockServer::start().await.unwrap();
+    server.push_answer(vec![
+        MockAction::Update("Par".to_string()),
+        MockAction::Update("Paris".to_string()),
+        MockAction::Final("Paris".to_string()),
+    ]);
+
+    let mut ai = server.new_conversation(Tone::Precise).await.unwrap();
+    ai.set_deltas(true);
+    ai.set_suggestions(false);
+
+    let responses = collect(&mut ai, PROMPT).await.unwrap();
+    assert_eq!(
+        responses,
+        vec![
+            SydneyResponse::StreamDelta("Par".to_string()),
+            SydneyResponse::StreamDelta("is".to_string()),
+            SydneyResponse::Quota { used: 1, max: 30 },
+            SydneyResponse::FinalText("Paris".to_string()),
+        ]
+    );
+    assert_eq!(server.prompts(), vec![PROMPT.to_string()]);
+    assert_eq!(ai.export_state().invocation_id, 1);
+}
+
+#[tokio::test]
+async fn citations_from_adaptive_cards() {
+    let server = MockServer::start().await.unwrap();
+    let cards = serde_json::json!([{ "body": [
+        { "type": "TextBlock", "text": "Paris[^1^] is the capital[^2^]." },
+        { "type": "Container", "items": [
+            { "type": "FactSet", "facts": [{ "title": "Population", "value": "2.1M" }] }
+        ]},
+        { "type": "RichTextBlock", "inlines": [{ "text": "Learn more" }] }
+    ]}]);
+    let message = serde_json::json!({
+        "text": "Paris is the capital.",
+        "author": "bot",
+        "adaptiveCards": cards,
+        "sourceAttributions": [
+            {
+                "providerDisplayName": "Paris - Wikipedia",
+                "seeMoreUrl": "https://en.wikipedia.org/wiki/Paris",
+                "provider": "search_web"
+            },
+            {
+                "providerDisplayName": "France",
+                "seeMoreUrl": "https://example.com/france",
+                "imageLink": "https://example.com/france.jpg",
+                "imageWidth": "120",
+                "imageHeight": 80
+            }
+        ]
+    });
+    server.push_answer(vec![
+        MockAction::Frame(serde_json::json!({
+            "type": 1,
+            "arguments": [{ "messages": [message] }]
+        })),
+        MockAction::Frame(serde_json::json!({
+            "type": 2,
+            "invocationId": "0",
+            "item": { "messages": [message], "result": { "value": "Success" } }
+        })),
+    ]);
+
+    let mut ai = server.new_conversation(Tone::Precise).await.unwrap();
+    ai.set_citations(true);
+    let responses = collect(&mut ai, PROMPT).await.unwrap();
+
+    let text = "Paris[^1^] is the capital[^2^].\n\nPopulation: 2.1M\n\nLearn more";
+    assert_eq!(responses[0], SydneyResponse::StreamText(text.to_string()));
+    let Some(SydneyResponse::Sources(citations)) = responses.get(1) else {
+        panic!("expected sources, got {responses:?}");
+    };
+    assert_eq!(citations.len(), 2);
+    assert_eq!(citations[0].url, "https://en.wikipedia.org/wiki/Paris");
+    let image = citations[1].image.as_ref().unwrap();
+    assert_eq!((image.width, image.height), (Some(120), Some(80)));
+    assert_eq!(
+        responses.last(),
+        Some(&SydneyResponse::FinalText(text.to_string()))
+    );
+
+    let rendered = bing_ai_rust::render_footnotes(text, citations);
+    assert!(rendered.starts_with("Paris[1] is the capital[2]."));
+    assert!(rendered.ends_with("[2]: https://example.com/france \"France\"\n"));
+}
+
+#[tokio::test]
+async fn keeps_conversation_across_turns() {
+    let server = MockServer::start().await.unwrap();
+    server.push_answer(vec![MockAction::Final("Paris".to_string())]);
+    server.push_answer(vec![MockAction::Final("Berlin".to_string())]);
+
+    let mut ai = server.new_conversation(Tone::Balanced).await.unwrap();
+    let first = ai.ask(PROMPT).await.unwrap().final_text().await.unwrap();
+    let second = ai
+        .ask("And of Germany?")
+        .await
+        .unwrap()
+        .final_text()
+        .await
+        .unwrap();
+
+    assert_eq!((first.as_str(), second.as_str()), ("Paris", "Berlin"));
+    assert_eq!(server.conversations_created(), 1);
+}
+
+#[tokio::test]
+async fn create_failure() {
+    let server = MockServer::start().await.unwrap();
+    server.set_create_result(Some("UnauthorizedRequest"));
+
+    let err = server.new_conversation(Tone::Precise).await.err().unwrap();
+    assert!(matches!(err, SydneyError::CreateConversationFailed(_)));
+}
+
+#[tokio::test]
+async fn throttled() {
+    let server = MockServer::start().await.unwrap();
+    server.push_answer(vec![MockAction::throttled()]);
+
+    let mut ai = server.new_conversation(Tone::Precise).await.unwrap();
+    let err = collect(&mut ai, PROMPT).await.unwrap_err();
+    let SydneyError::Throttled(info) = err else {
+        panic!("expected throttled error, got {err:?}");
+    };
+    assert_eq!(info.message, "Request is throttled.");
+    assert_eq!(info.challenge_url, None);
+    assert_eq!(info.retry_after, Some(Duration::from_secs(60)));
+}
+
+#[tokio::test]
+async fn captcha() {
+    let server = MockServer::start().await.unwrap();
+    server.push_answer(vec![MockAction::captcha()]);
+
+    let mut ai = server.new_conversation(Tone::Precise).await.unwrap();
+    let err = collect(&mut ai, PROMPT).await.unwrap_err();
+    let SydneyError::CaptchaRequired(info) = err else {
+        panic!("expected captcha error, got {err:?}");
+    };
+    assert_eq!(info.message, "User needs to solve CAPTCHA to continue.");
+    assert_eq!(
+        info.challenge_url.as_deref(),
+        Some("https://www.bing.com/turing/captcha/challenge")
+    );
+}
+
+#[tokio::test]
+async fn max_messages() {
+    let server = MockServer::start().await.unwrap();
+    server.push_answer(vec![MockAction::max_messages(1, "Paris")]);
+
+    let mut ai = server.new_conversation(Tone::Precise).await.unwrap();
+    let err = collect(&mut ai, PROMPT).await.unwrap_err();
+    assert!(matches!(err, SydneyError::MaxMessagesCountLimitReached));
+}
+
+#[tokio::test]
+async fn server_error_in_close_frame() {
+    let server = MockServer::start().await.unwrap();
+    server.push_answer(vec![MockAction::Close {
+        error: Some("Internal error".to_string()),
+        allow_reconnect: false,
+    }]);
+
+    let mut ai = server.new_conversation(Tone::Precise).await.unwrap();
+    let err = collect(&mut ai, PROMPT).await.unwrap_err();
+    assert!(matches!(err, SydneyError::ServerError(msg) if msg == "Internal error"));
+}
+
+#[tokio::test]
+async fn completion_error() {
+    let server = MockServer::start().await.unwrap();
+    server.push_answer(vec![
+        MockAction::Update("Par".to_string()),
+        MockAction::Completion {
+            error: Some("Internal error".to_string()),
+        },
+    ]);
+    server.push_answer(vec![MockAction::Final("Paris".to_string())]);
+
+    let mut ai = server.new_conversation(Tone::Precise).await.unwrap();
+    let err = collect(&mut ai, PROMPT).await.unwrap_err();
+    assert!(matches!(err, SydneyError::ServerError(msg) if msg == "Internal error"));
+
+    let answer = ai.ask(PROMPT).await.unwrap().final_text().await.unwrap();
+    assert_eq!(answer, "Paris");
+}
+
+#[tokio::test]
+async fn completion_ends_answer_without_result() {
+    let server = MockServer::start().await.unwrap();
+    server.push_answer(vec![
+        MockAction::Update("Paris".to_string()),
+        MockAction::Completion { error: None },
+    ]);
+
+    let mut ai = server.new_conversation(Tone::Precise).await.unwrap();
+    let responses = collect(&mut ai, PROMPT).await.unwrap();
+    assert_eq!(
+        responses,
+        vec![SydneyResponse::StreamText("Paris".to_string())]
+    );
+}
+
+#[tokio::test]
+async fn stale_completion_is_ignored() {
+    let server = MockServer::start().await.unwrap();
+    server.push_answer(vec![MockAction::Final("Paris".to_string())]);
+    server.push_answer(vec![
+        // Late completion of the previous invocation
+        MockAction::Frame(serde_json::json!({ "type": 3, "invocationId": "0" })),
+        MockAction::Update("Berlin".to_string()),
+        MockAction::Final("Berlin".to_string()),
+    ]);
+
+    let mut ai = server.new_conversation(Tone::Precise).await.unwrap();
+    ai.ask(PROMPT).await.unwrap().final_text().await.unwrap();
+
+    let responses = collect(&mut ai, "And of Germany?").await.unwrap();
+    assert_eq!(
+        responses.first(),
+        Some(&SydneyResponse::StreamText("Berlin".to_string()))
+    );
+    assert_eq!(
+        responses.last(),
+        Some(&SydneyResponse::FinalText("Berlin".to_string()))
+    );
+}
+
+#[tokio::test]
+async fn close_allowing_reconnect() {
+    let server = MockServer::start().await.unwrap();
+    let close = MockAction::Close {
+        error: None,
+        allow_reconnect: true,
+    };
+    server.push_answer(vec![close.clone()]);
+    server.push_answer(vec![MockAction::Final("Paris".to_string())]);
+    server.push_answer(vec![close]);
+
+    let mut ai = server.new_conversation(Tone::Precise).await.unwrap();
+    ai.set_reconnect_policy(Some(ReconnectPolicy {
+        delay: Duration::from_millis(10),
+        ..Default::default()
+    }));
+    let answer = ai.ask(PROMPT).await.unwrap().final_text().await.unwrap();
+    assert_eq!(answer, "Paris");
+    assert_eq!(server.prompts().len(), 2);
+
+    // Without policy the answer just ends
+    ai.set_reconnect_policy(None);
+    let err = collect(&mut ai, PROMPT).await.unwrap_err();
+    assert!(matches!(err, SydneyError::Disconnected));
+}
+
+#[tokio::test]
+async fn disconnect_without_reconnect_policy() {
+    let server = MockServer::start().await.unwrap();
+    server.push_answer(vec![
+        MockAction::Update("Par".to_string()),
+        MockAction::Disconnect,
+    ]);
+    server.push_answer(vec![MockAction::Final("Paris".to_string())]);
+
+    let mut ai = server.new_conversation(Tone::Precise).await.unwrap();
+    let err = collect(&mut ai, PROMPT).await.unwrap_err();
+    assert!(matches!(err, SydneyError::Disconnected));
+
+    // Client stays usable after the disconnect
+    let answer = ai.ask(PROMPT).await.unwrap().final_text().await.unwrap();
+    assert_eq!(answer, "Paris");
+}
+
+#[tokio::test]
+async fn disconnect_reissues_unanswered_ask() {
+    let server = MockServer::start().await.unwrap();
+    server.push_answer(vec![MockAction::Disconnect]);
+    server.push_answer(vec![MockAction::Final("Paris".to_string())]);
+
+    let mut ai = server.new_conversation(Tone::Precise).await.unwrap();
+    ai.set_reconnect_policy(Some(ReconnectPolicy {
+        delay: Duration::from_millis(10),
+        ..Default::default()
+    }));
+
+    let answer = ai.ask(PROMPT).await.unwrap().final_text().await.unwrap();
+    assert_eq!(answer, "Paris");
+    assert_eq!(
+        server.prompts(),
+        vec![PROMPT.to_string(), PROMPT.to_string()]
+    );
+}
+
+#[tokio::test]
+async fn disconnect_reissues_ask_at_most_max_retries() {
+    let server = MockServer::start().await.unwrap();
+    for _ in 0..20 {
+        server.push_answer(vec![MockAction::Disconnect]);
+    }
+
+    let mut ai = server.new_conversation(Tone::Precise).await.unwrap();
+    ai.set_reconnect_policy(Some(ReconnectPolicy {
+        max_retries: 1,
+        delay: Duration::from_millis(10),
+        ..Default::default()
+    }));
+
+    let result = tokio::time::timeout(Duration::from_secs(3), collect(&mut ai, PROMPT))
+        .await
+        .expect("stream kept re-sending the prompt");
+    assert!(matches!(result, Err(SydneyError::Disconnected)));
+    assert_eq!(server.prompts().len(), 2);
+}
+
+#[tokio::test]
+async fn reconnects_after_drop_between_asks() {
+    for policy in [None, Some(ReconnectPolicy::default())] {
+        let server = MockServer::start().await.unwrap();
+        server.push_answer(vec![
+            MockAction::Final("Paris".to_string()),
+            MockAction::Disconnect,
+        ]);
+        server.push_answer(vec![MockAction::Final("Berlin".to_string())]);
+        server.push_answer(vec![MockAction::Final("Rome".to_string())]);
+
+        let mut ai = server.new_conversation(Tone::Precise).await.unwrap();
+        ai.set_reconnect_policy(policy);
+        ai.ask(PROMPT).await.unwrap().final_text().await.unwrap();
+        // Let the socket drop while idle
+        tokio::time::sleep(Duration::from_millis(100)).await;
+
+        for expected in ["Berlin", "Rome"] {
+            let answer = ai.ask(PROMPT).await.unwrap().final_text().await.unwrap();
+            assert_eq!(answer, expected);
+        }
+        assert_eq!(server.prompts().len(), 3);
+    }
+}
+
+#[tokio::test]
+async fn answers_ping() {
+    let server = MockServer::start().await.unwrap();
+    server.push_answer(vec![
+        MockAction::Ping,
+        MockAction::Delay(Duration::from_millis(50)),
+        MockAction::Final("Paris".to_string()),
+    ]);
+
+    let mut ai = server.new_conversation(Tone::Precise).await.unwrap();
+    ai.ask(PROMPT).await.unwrap().final_text().await.unwrap();
+
+    for _ in 0..100 {
+        if server.client_frames().iter().any(|f| f["type"] == 6) {
+            return;
+        }
+        tokio::time::sleep(Duration::from_millis(10)).await;
+    }
+    panic!("ping was not answered");
+}
+
+#[tokio::test]
+async fn start_new_uses_same_endpoints() {
+    let server = MockServer::start().await.unwrap();
+    server.push_answer(vec![MockAction::Final("Paris".to_string())]);
+
+    let ai = server
+        .builder()
+        .bundle_version("1.0.0")
+        .header("X-Test", "1")
+        .user_agent("mock-agent/1.0")
+        .new_conversation(Tone::Precise)
+        .await
+        .unwrap();
+    let mut ai = ai.start_new(Tone::Creative).await.unwrap();
+
+    let answer = ai.ask(PROMPT).await.unwrap().final_text().await.unwrap();
+    assert_eq!(answer, "Paris");
+    assert_eq!(ai.conversation_id(), "mock-conversation-2");
+    assert_eq!(ai.tone(), Tone::Creative);
+
+    let creates = server.create_requests();
+    assert_eq!(creates.len(), 2);
+    let handshakes = server.handshakes();
+    assert_eq!(handshakes.len(), 1);
+
+    for create in &creates {
+        assert!(
+            create.path.ends_with("?bundleVersion=1.0.0"),
+            "{}",
+            create.path
+        );
+    }
+    for request in creates.iter().chain(&handshakes) {
+        assert_eq!(request.header("x-test"), Some("1"));
+        assert_eq!(request.header("user-agent"), Some("mock-agent/1.0"));
+    }
+}
+
+#[tokio::test]
+async fn start_new_keeps_policies() {
+    let server = MockServer::start().await.unwrap();
+    server.push_answer(vec![MockAction::Disconnect]);
+    server.push_answer(vec![MockAction::Final("Paris".to_string())]);
+
+    let dir = std::env::temp_dir().join(format!("bing-ai-start-new-{}", std::process::id()));
+    let mut ai = server.new_conversation(Tone::Precise).await.unwrap();
+    ai.set_transcript_store(Some(TranscriptStore::new(&dir)));
+    ai.set_reconnect_policy(Some(ReconnectPolicy {
+        delay: Duration::from_millis(10),
+        ..Default::default()
+    }));
+
+    let mut ai = ai.start_new(Tone::Creative).await.unwrap();
+    let answer = ai.ask(PROMPT).await.unwrap().final_text().await.unwrap();
+    assert_eq!(answer, "Paris");
+
+    let transcript = TranscriptStore::new(&dir).load("mock-conversation-2");
+    _ = std::fs::remove_dir_all(&dir);
+    assert_eq!(transcript.unwrap().unwrap().turns[0].answer, "Paris");
+}
+
+#[tokio::test]
+async fn resume_saved_conversation() {
+    let server = MockServer::start().await.unwrap();
+    server.push_answer(vec![MockAction::Final("Paris".to_string())]);
+    server.push_answer(vec![MockAction::Final("Berlin".to_string())]);
+
+    let dir = std::env::temp_dir().join(format!("bing-ai-resume-{}", std::process::id()));
+    let store = ConversationStore::new(&dir);
+
+    let mut ai = server.new_conversation(Tone::Creative).await.unwrap();
+    ai.ask(PROMPT).await.unwrap().final_text().await.unwrap();
+    store.save(&ai.export_state()).unwrap();
+    drop(ai);
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        let path = dir.join("mock-conversation-1.json");
+        let mode = std::fs::metadata(path).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o600);
+    }
+
+    let state = store.latest().unwrap().unwrap();
+    assert_eq!(state.invocation_id, 1);
+    let mut ai = server.resume(state).unwrap();
+    let answer = ai
+        .ask("And of Germany?")
+        .await
+        .unwrap()
+        .final_text()
+        .await
+        .unwrap();
+    assert_eq!(answer, "Berlin");
+    assert_eq!(ai.tone(), Tone::Creative);
+    assert_eq!(ai.conversation_id(), "mock-conversation-1");
+    assert_eq!(server.conversations_created(), 1);
+
+    let asks: Vec<_> = server
+        .client_frames()
+        .into_iter()
+        .filter(|f| f["type"] == 4)
+        .collect();
+    assert_eq!(asks[1]["invocationId"], "1");
+    assert_eq!(
+        asks[1]["arguments"][0]["conversationId"],
+        "mock-conversation-1"
+    );
+
+    assert!(store.remove("mock-conversation-1").unwrap());
+    assert!(!store.remove("mock-conversation-1").unwrap());
+    assert!(store.list().unwrap().is_empty());
+    _ = std::fs::remove_dir_all(&dir);
+}
+
+#[tokio::test]
+async fn records_and_searches_transcripts() {
+    let server = MockServer::start().await.unwrap();
+    server.push_answer(vec![MockAction::Final("Paris".to_string())]);
+    server.push_answer(vec![MockAction::Final("Berlin".to_string())]);
+
+    let dir = std::env::temp_dir().join(format!("bing-ai-transcripts-{}", std::process::id()));
+    let store = TranscriptStore::new(&dir);
+
+    let mut ai = server.new_conversation(Tone::Precise).await.unwrap();
+    ai.set_transcript_store(Some(store.clone()));
+    for prompt in [PROMPT, "And of Germany?"] {
+        ai.ask(prompt).await.unwrap().final_text().await.unwrap();
+    }
+
+    let transcript = store.load("mock-conversation-1").unwrap().unwrap();
+    let answers: Vec<_> = transcript.turns.iter().map(|t| t.answer.as_str()).collect();
+    assert_eq!(answers, ["Paris", "Berlin"]);
+    assert_eq!(transcript.turns[1].prompt, "And of Germany?");
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        let path = dir.join("mock-conversation-1.json");
+        let mode = std::fs::metadata(path).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o600);
+    }
+
+    // Case insensitive, in both prompts and answers
+    let hits = store.search("GERMANY").unwrap();
+    assert_eq!(hits.len(), 1);
+    assert_eq!(hits[0].conversation_id, "mock-conversation-1");
+    assert_eq!(hits[0].turn_index, 1);
+    assert_eq!(store.search("paris").unwrap()[0].turn_index, 0);
+    assert!(store.search("Rome").unwrap().is_empty());
+
+    assert!(store.remove("mock-conversation-1").unwrap());
+    assert!(!store.remove("mock-conversation-1").unwrap());
+    assert!(store.search("paris").unwrap().is_empty());
+    _ = std::fs::remove_dir_all(&dir);
+}
+
+/// Minimal SOCKS5 proxy without authentication, returns its address and
+/// counter of tunneled connections.
+async fn start_socks5_proxy() -> (String, Arc<AtomicUsize>) {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let connections = Arc::new(AtomicUsize::new(0));
+
+    let counter = connections.clone();
+    tokio::spawn(async move {
+        while let Ok((mut client, _)) = listener.accept().await {
+            counter.fetch_add(1, Ordering::SeqCst);
+            tokio::spawn(async move {
+                let mut buf = [0u8; 262];
+                client.read_exact(&mut buf[..2]).await?;
+                let methods = buf[1] as usize;
+                client.read_exact(&mut buf[..methods]).await?;
+                client.write_all(&[5, 0]).await?;
+
+                client.read_exact(&mut buf[..4]).await?;
+                let host = match buf[3] {
+                    1 => {
+                        client.read_exact(&mut buf[..4]).await?;
+                        format!("{}.{}.{}.{}", buf[0], buf[1], buf[2], buf[3])
+                    }
+                    _ => {
+                        let len = client.read_u8().await? as usize;
+                        client.read_exact(&mut buf[..len]).await?;
+                        String::from_utf8_lossy(&buf[..len]).to_string()
+                    }
+                };
+                let port = client.read_u16().await?;
+
+                let mut target = TcpStream::connect((host.as_str(), port)).await?;
+                client.write_all(&[5, 0, 0, 1, 0, 0, 0, 0, 0, 0]).await?;
+                tokio::io::copy_bidirectional(&mut client, &mut target).await?;
+                std::io::Result::Ok(())
+            });
+        }
+    });
+
+    (format!("socks5h://{addr}"), connections)
+}
+
+/// HTTP proxy answering `CONNECT` with `connect_status`, forwarding plain requests.
+/// Records the head of every request it gets.
+async fn start_http_proxy(connect_status: &'static str) -> (String, Arc<Mutex<Vec<String>>>) {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let requests = Arc::new(Mutex::new(Vec::new()));
+
+    let recorded = requests.clone();
+    tokio::spawn(async move {
+        while let Ok((mut client, _)) = listener.accept().await {
+            let recorded = recorded.clone();
+            tokio::spawn(async move {
+                let mut head = Vec::new();
+                while !head.ends_with(b"\r\n\r\n") {
+                    head.push(client.read_u8().await?);
+                }
+                let head = String::from_utf8_lossy(&head).to_string();
+                recorded.lock().unwrap().push(head.clone());
+
+                let (line, rest) = head.split_once("\r\n").unwrap_or_default();
+                let mut parts = line.split_whitespace();
+                let (method, target) = (
+                    parts.next().unwrap_or_default(),
+                    parts.next().unwrap_or_default(),
+                );
+
+                let mut upstream = if method == "CONNECT" {
+                    let response = format!("HTTP/1.1 {connect_status}\r\n\r\n");
+                    client.write_all(response.as_bytes()).await?;
+                    if !connect_status.starts_with("200") {
+                        return Ok(());
+                    }
+                    TcpStream::connect(target).await?
+                } else {
+                    let target = target.trim_start_matches("http://");
+                    let (host, path) = target.split_at(target.find('/').unwrap_or(target.len()));
+                    let mut upstream = TcpStream::connect(host).await?;
+                    let request = format!("{method} {path} HTTP/1.1\r\n{rest}");
+                    upstream.write_all(request.as_bytes()).await?;
+                    upstream
+                };
+                tokio::io::copy_bidirectional(&mut client, &mut upstream).await?;
+                std::io::Result::Ok(())
+            });
+        }
+    });
+
+    (format!("http://{addr}"), requests)
+}
+
+fn connect_requests(requests: &Mutex<Vec<String>>) -> Vec<String> {
+    let requests = requests.lock().unwrap();
+    requests
+        .iter()
+        .filter(|head| head.starts_with("CONNECT "))
+        .cloned()
+        .collect()
+}
+
+#[tokio::test]
+async fn socks5_proxy_for_create_and_websocket() {
+    let server = MockServer::start().await.unwrap();
+    server.push_answer(vec![MockAction::Final("Paris".to_string())]);
+    let (proxy_url, connections) = start_socks5_proxy().await;
+
+    let mut ai = server
+        .builder()
+        .proxy(Proxy::new(&proxy_url).unwrap())
+        .new_conversation(Tone::Precise)
+        .await
+        .unwrap();
+    let answer = ai.ask(PROMPT).await.unwrap().final_text().await.unwrap();
+
+    assert_eq!(answer, "Paris");
+    assert_eq!(connections.load(Ordering::SeqCst), 2);
+}
+
+#[tokio::test]
+async fn proxy_bypassed_for_no_proxy_hosts() {
+    let server = MockServer::start().await.unwrap();
+    server.push_answer(vec![MockAction::Final("Paris".to_string())]);
+    let (proxy_url, connections) = start_socks5_proxy().await;
+
+    let proxy = Proxy::new(&proxy_url)
+        .unwrap()
+        .no_proxy("localhost, 127.0.0.1");
+    let mut ai = server
+        .builder()
+        .proxy(proxy)
+        .new_conversation(Tone::Precise)
+        .await
+        .unwrap();
+    let answer = ai.ask(PROMPT).await.unwrap().final_text().await.unwrap();
+
+    assert_eq!(answer, "Paris");
+    assert_eq!(connections.load(Ordering::SeqCst), 0);
+}
+
+#[tokio::test]
+async fn http_proxy_tunnels_websocket() {
+    let server = MockServer::start().await.unwrap();
+    server.push_answer(vec![MockAction::Final("Paris".to_string())]);
+    let (proxy_url, requests) = start_http_proxy("200 Connection established").await;
+
+    let mut ai = server
+        .builder()
+        .proxy(Proxy::new(&proxy_url).unwrap())
+        .new_conversation(Tone::Precise)
+        .await
+        .unwrap();
+    let answer = ai.ask(PROMPT).await.unwrap().final_text().await.unwrap();
+
+    assert_eq!(answer, "Paris");
+    assert_eq!(requests.lock().unwrap().len(), 2);
+    let connects = connect_requests(&requests);
+    assert_eq!(connects.len(), 1);
+    let ws_url = server.ws_url();
+    let target = ws_url
+        .trim_start_matches("ws://")
+        .split('/')
+        .next()
+        .unwrap();
+    assert!(connects[0].starts_with(&format!("CONNECT {target} HTTP/1.1\r\n")));
+    assert!(!connects[0].contains("Proxy-Authorization"));
+}
+
+#[tokio::test]
+async fn http_proxy_sends_credentials() {
+    let server = MockServer::start().await.unwrap();
+    server.push_answer(vec![MockAction::Final("Paris".to_string())]);
+    let (proxy_url, requests) = start_http_proxy("200 Connection established").await;
+    let proxy_url = proxy_url.replace("http://", "http://user:p%40ss@");
+
+    let mut ai = server
+        .builder()
+        .proxy(Proxy::new(&proxy_url).unwrap())
+        .new_conversation(Tone::Precise)
+        .await
+        .unwrap();
+    let answer = ai.ask(PROMPT).await.unwrap().final_text().await.unwrap();
+
+    assert_eq!(answer, "Paris");
+    let connects = connect_requests(&requests);
+    assert_eq!(connects.len(), 1);
+    // base64 of "user:p@ss"
+    assert!(connects[0].contains("Proxy-Authorization: Basic dXNlcjpwQHNz\r\n"));
+}
+
+#[tokio::test]
+async fn http_proxy_refused_connect() {
+    let server = MockServer::start().await.unwrap();
+    server.push_answer(vec![MockAction::Final("Paris".to_string())]);
+    let (proxy_url, requests) = start_http_proxy("407 Proxy Authentication Required").await;
+
+    let mut ai = server
+        .builder()
+        .proxy(Proxy::new(&proxy_url).unwrap())
+        .new_conversation(Tone::Precise)
+        .await
+        .unwrap();
+    let err = ai.ask(PROMPT).await.err().unwrap();
+
+    assert!(
+        matches!(&err, SydneyError::ProxyError(msg) if msg.contains("407")),
+        "{err:?}"
+    );
+    assert_eq!(connect_requests(&requests).len(), 1);
+}
+
+#[test]
+fn rejects_unsupported_proxy_scheme() {
+    let err = Proxy::new("ftp://proxy.local:21").unwrap_err();
+    assert!(matches!(err, SydneyError::ProxyError(_)));
+}
+
+fn short_timeouts() -> Timeouts {
+    let limit = Some(Duration::from_millis(100));
+    Timeouts {
+        create: limit,
+        handshake: limit,
+        first_message: limit,
+        idle: limit,
+    }
+}
+
+#[tokio::test]
+async fn create_timeout() {
+    let server = MockServer::start().await.unwrap();
+    server.set_delay(Duration::from_millis(500));
+
+    let err = server
+        .builder()
+        .timeouts(short_timeouts())
+        .new_conversation(Tone::Precise)
+        .await
+        .err()
+        .unwrap();
+    assert!(matches!(err, SydneyError::Timeout(TimeoutKind::Create)));
+}
+
+#[tokio::test]
+async fn handshake_timeout() {
+    let server = MockServer::start().await.unwrap();
+    let mut ai = server
+        .builder()
+        .timeouts(short_timeouts())
+        .new_conversation(Tone::Precise)
+        .await
+        .unwrap();
+
+    server.set_delay(Duration::from_millis(500));
+    let err = ai.ask(PROMPT).await.err().unwrap();
+    assert!(matches!(err, SydneyError::Timeout(TimeoutKind::Handshake)));
+}
+
+#[tokio::test]
+async fn first_message_timeout_keeps_client_usable() {
+    let server = MockServer::start().await.unwrap();
+    server.push_answer(vec![
+        MockAction::Delay(Duration::from_millis(500)),
+        MockAction::Final("Late".to_string()),
+    ]);
+    server.push_answer(vec![MockAction::Final("Paris".to_string())]);
+
+    let mut ai = server
+        .builder()
+        .timeouts(short_timeouts())
+        .new_conversation(Tone::Precise)
+        .await
+        .unwrap();
+
+    let err = collect(&mut ai, PROMPT).await.unwrap_err();
+    assert!(matches!(
+        err,
+        SydneyError::Timeout(TimeoutKind::FirstMessage)
+    ));
+
+    let answer = ai.ask(PROMPT).await.unwrap().final_text().await.unwrap();
+    assert_eq!(answer, "Paris");
+    assert_eq!(ai.export_state().invocation_id, 2);
+}
+
+#[tokio::test]
+async fn idle_timeout() {
+    let server = MockServer::start().await.unwrap();
+    server.push_answer(vec![
+        MockAction::Update("Par".to_string()),
+        MockAction::Delay(Duration::from_millis(500)),
+        MockAction::Final("Paris".to_string()),
+    ]);
+
+    let mut ai = server
+        .builder()
+        .timeouts(short_timeouts())
+        .new_conversation(Tone::Precise)
+        .await
+        .unwrap();
+
+    let err = collect(&mut ai, PROMPT).await.unwrap_err();
+    assert!(matches!(err, SydneyError::Timeout(TimeoutKind::Idle)));
+}
+
+#[tokio::test]
+async fn cancel_stops_answer_and_keeps_conversation() {
+    let server = MockServer::start().await.unwrap();
+    server.push_answer(vec![
+        MockAction::Update("Par".to_string()),
+        MockAction::Delay(Duration::from_millis(300)),
+        MockAction::Final("Paris".to_string()),
+    ]);
+    server.push_answer(vec![MockAction::Final("Berlin".to_string())]);
+
+    let mut ai = server.new_conversation(Tone::Precise).await.unwrap();
+    let mut stream = ai.ask(PROMPT).await.unwrap();
+    let first = stream.next().await.unwrap().unwrap();
+    assert_eq!(first, SydneyResponse::StreamText("Par".to_string()));
+
+    stream.cancel_handle().cancel();
+    assert!(stream.next().await.is_none());
+    drop(stream);
+
+    let answer = ai
+        .ask("And of Germany?")
+        .await
+        .unwrap()
+        .final_text()
+        .await
+        .unwrap();
+    assert_eq!(answer, "Berlin");
+    assert_eq!(ai.export_state().invocation_id, 2);
+
+    for _ in 0..100 {
+        let frames = server.client_frames();
+        if frames
+            .iter()
+            .any(|f| f["type"] == 5 && f["invocationId"] == "0")
+        {
+            return;
+        }
+        tokio::time::sleep(Duration::from_millis(10)).await;
+    }
+    panic!("cancel invocation was not sent");
+}
+
+#[tokio::test]
+async fn cancel_without_answer_is_noop() {
+    let server = MockServer::start().await.unwrap();
+    server.push_answer(vec![MockAction::Final("Paris".to_string())]);
+
+    let mut ai = server.new_conversation(Tone::Precise).await.unwrap();
+    ai.cancel();
+    let answer = ai.ask(PROMPT).await.unwrap().final_text().await.unwrap();
+    assert_eq!(answer, "Paris");
+}
+
+#[tokio::test]
+async fn ask_with_uploaded_image() {
+    let server = MockServer::start().await.unwrap();
+    server.push_answer(vec![MockAction::Final("A cat".to_string())]);
+
+    let mut ai = server.new_conversation(Tone::Precise).await.unwrap();
+    let answer = ai
+        .ask_with_image("What is in the image?", vec![0x89, b'P', b'N', b'G'])
+        .await
+        .unwrap()
+        .final_text()
+        .await
+        .unwrap();
+    assert_eq!(answer, "A cat");
+
+    let uploads = server.uploads();
+    assert_eq!(uploads.len(), 1);
+    assert!(uploads[0].contains("name=\"imageBase64\""));
+    assert!(uploads[0].contains("iVBORw=="));
+    assert!(uploads[0].contains("mock-conversation-1"));
+
+    let frames = server.client_frames();
+    let message = &frames.iter().find(|f| f["type"] == 4).unwrap()["arguments"][0]["message"];
+    assert_eq!(
+        message["imageUrl"],
+        "https://www.bing.com/images/blob?bcid=mock-processed-blob"
+    );
+    assert_eq!(
+        message["originalImageUrl"],
+        "https://www.bing.com/images/blob?bcid=mock-blob"
+    );
+}
+
+#[tokio::test]
+async fn ask_with_image_url() {
+    let server = MockServer::start().await.unwrap();
+    server.push_answer(vec![MockAction::Final("A dog".to_string())]);
+
+    let mut ai = server.new_conversation(Tone::Precise).await.unwrap();
+    ai.ask_with_image("What is in the image?", "https://example.com/dog.png")
+        .await
+        .unwrap()
+        .final_text()
+        .await
+        .unwrap();
+
+    let uploads = server.uploads();
+    assert!(uploads[0].contains("https://example.com/dog.png"));
+    assert!(!uploads[0].contains("imageBase64"));
+    assert_eq!(
+        ImageInput::from("shot.png"),
+        ImageInput::Path("shot.png".into())
+    );
+}
+
+#[tokio::test]
+async fn image_generation_request_and_creator() {
+    let server = MockServer::start().await.unwrap();
+    server.push_answer(vec![
+        MockAction::Update("I'll try to create that.".to_string()),
+        MockAction::generate_image("a cat in space"),
+        MockAction::Final("I'll try to create that.".to_string()),
+    ]);
+
+    let mut ai = server.new_conversation(Tone::Creative).await.unwrap();
+    let responses = collect(&mut ai, "Draw a cat in space").await.unwrap();
+    let requests: Vec<_> = responses
+        .iter()
+        .filter(|r| matches!(r, SydneyResponse::ImageGenerationRequest { .. }))
+        .collect();
+    assert_eq!(
+        requests,
+        vec![&SydneyResponse::ImageGenerationRequest {
+            prompt: "a cat in space".to_string()
+        }]
+    );
+    assert_eq!(
+        responses.last(),
+        Some(&SydneyResponse::FinalText(
+            "I'll try to create that.".to_string()
+        ))
+    );
+
+    let mut creator = ai.image_creator();
+    creator.set_poll_interval(Duration::from_millis(10));
+    let urls = creator.create("a cat in space").await.unwrap();
+    assert_eq!(urls.len(), 2);
+    assert!(urls[0].ends_with("/th/id/OIG.mock1"));
+    assert_eq!(server.image_prompts(), vec!["a cat in space".to_string()]);
+
+    let dir = std::env::temp_dir().join(format!("bing-ai-images-{}", std::process::id()));
+    let paths = creator.download(&urls, &dir).await.unwrap();
+    assert_eq!(paths[0].file_name().unwrap(), "OIG_mock1.jpg");
+    assert_eq!(std::fs::read(&paths[1]).unwrap(), b"mock image");
+    std::fs::remove_dir_all(dir).unwrap();
+}
+
+#[tokio::test]
+async fn image_creator_rejected_prompt() {
+    let server = MockServer::start().await.unwrap();
+    let creator = server.builder().image_creator().unwrap();
+
+    let err = creator.create("rejected prompt").await.unwrap_err();
+    assert!(matches!(err, SydneyError::ImageGenerationFailed(_)));
+}
+
+#[tokio::test]
+async fn quota_with_every_answer() {
+    let server = MockServer::start().await.unwrap();
+    server.push_answer(vec![MockAction::Final("Paris".to_string())]);
+    server.push_answer(vec![MockAction::Final("Berlin".to_string())]);
+
+    let mut ai = server.new_conversation(Tone::Precise).await.unwrap();
+    assert_eq!(ai.quota(), None);
+
+    let responses = collect(&mut ai, PROMPT).await.unwrap();
+    assert!(responses.contains(&SydneyResponse::Quota { used: 1, max: 30 }));
+
+    let responses = collect(&mut ai, "And of Germany?").await.unwrap();
+    assert!(responses.contains(&SydneyResponse::Quota { used: 2, max: 30 }));
+    assert_eq!(ai.quota(), Some((2, 30)));
+}
+
+#[tokio::test]
+async fn rollover_after_last_allowed_answer() {
+    let server = MockServer::start().await.unwrap();
+    server.push_answer(vec![MockAction::max_messages(1, "Paris")]);
+    server.push_answer(vec![MockAction::Final("Berlin".to_string())]);
+
+    let mut ai = server.new_conversation(Tone::Precise).await.unwrap();
+    ai.set_rollover_policy(Some(RolloverPolicy::default()));
+
+    let answer = ai.ask(PROMPT).await.unwrap().final_text().await.unwrap();
+    assert_eq!(answer, "Paris");
+
+    let responses = collect(&mut ai, "And of Germany?").await.unwrap();
+    assert_eq!(
+        responses[0],
+        SydneyResponse::Rollover {
+            previous_conversation_id: "mock-conversation-1".to_string(),
+            conversation_id: "mock-conversation-2".to_string(),
+        }
+    );
+    assert_eq!(
+        responses.last(),
+        Some(&SydneyResponse::FinalText("Berlin".to_string()))
+    );
+    assert_eq!(ai.conversation_id(), "mock-conversation-2");
+    assert_eq!(ai.export_state().invocation_id, 1);
+
+    let frames = server.client_frames();
+    let ask = &frames.iter().filter(|f| f["type"] == 4).nth(1).unwrap()["arguments"][0];
+    assert_eq!(ask["conversationId"], "mock-conversation-2");
+    assert_eq!(ask["isStartOfSession"], true);
+    let context = ask["previousMessages"][0]["description"].as_str().unwrap();
+    assert!(context.contains(PROMPT) && context.contains("Paris"));
+}
+
+#[tokio::test]
+async fn rollover_reasks_rejected_prompt() {
+    let server = MockServer::start().await.unwrap();
+    server.push_answer(vec![MockAction::limit_reached()]);
+    server.push_answer(vec![MockAction::Final("Paris".to_string())]);
+
+    let mut ai = server.new_conversation(Tone::Precise).await.unwrap();
+    ai.set_rollover_policy(Some(RolloverPolicy::default()));
+
+    let responses = collect(&mut ai, PROMPT).await.unwrap();
+    assert!(matches!(responses[0], SydneyResponse::Rollover { .. }));
+    assert_eq!(
+        responses.last(),
+        Some(&SydneyResponse::FinalText("Paris".to_string()))
+    );
+    assert_eq!(
+        server.prompts(),
+        vec![PROMPT.to_string(), PROMPT.to_string()]
+    );
+    assert_eq!(server.conversations_created(), 2);
+}
+
+#[tokio::test]
+async fn rollover_at_most_once_per_ask() {
+    let server = MockServer::start().await.unwrap();
+    server.push_answer(vec![MockAction::limit_reached()]);
+    server.push_answer(vec![MockAction::limit_reached()]);
+    server.push_answer(vec![MockAction::Final("Paris".to_string())]);
+
+    let mut ai = server.new_conversation(Tone::Precise).await.unwrap();
+    ai.set_rollover_policy(Some(RolloverPolicy::default()));
+
+    let err = collect(&mut ai, PROMPT).await.unwrap_err();
+    assert!(matches!(err, SydneyError::MaxMessagesCountLimitReached));
+    assert_eq!(server.conversations_created(), 2);
+}
+
+#[tokio::test]
+async fn cancel_after_rollover_reask() {
+    let server = MockServer::start().await.unwrap();
+    server.push_answer(vec![MockAction::limit_reached()]);
+    server.push_answer(vec![
+        MockAction::Update("Par".to_string()),
+        MockAction::Delay(Duration::from_millis(1000)),
+        MockAction::Final("Paris".to_string()),
+    ]);
+
+    let mut ai = server.new_conversation(Tone::Precise).await.unwrap();
+    ai.set_rollover_policy(Some(RolloverPolicy::default()));
+
+    let mut stream = ai.ask(PROMPT).await.unwrap();
+    let rollover = stream.next().await.unwrap().unwrap();
+    assert!(matches!(rollover, SydneyResponse::Rollover { .. }));
+    let first = stream.next().await.unwrap().unwrap();
+    assert_eq!(first, SydneyResponse::StreamText("Par".to_string()));
+
+    stream.cancel();
+    let next = tokio::time::timeout(Duration::from_millis(500), stream.next())
+        .await
+        .expect("stream was not cancelled");
+    assert!(next.is_none());
+
+    // Mock reads the cancel frame only after its delay
+    for _ in 0..200 {
+        if server.client_frames().iter().any(|f| f["type"] == 5) {
+            return;
+        }
+        tokio::time::sleep(Duration::from_millis(10)).await;
+    }
+    panic!("cancel invocation was not sent");
+}
+
+const NETSCAPE_COOKIES: &str = "# Netscape HTTP Cookie File
+.bing.com\tTRUE\t/\tTRUE\t1893456000\t_U\tauth
+#HttpOnly_www.bing.com\tFALSE\t/\tTRUE\t0\tSRCHHPGUSR\tlang
+.example.com\tTRUE\t/\tFALSE\t1893456000\t_U\tother
+";
+
+#[test]
+fn netscape_cookies_filtered_to_bing() {
+    let cookies = BrowserCookies::from_netscape(NETSCAPE_COOKIES).unwrap();
+    assert_eq!(cookies.to_header(), "_U=auth; SRCHHPGUSR=lang");
+    assert_eq!(cookies.cookies()[0].expires, Some(1893456000));
+    assert_eq!(cookies.cookies()[1].expires, None);
+    assert!(cookies.validate().is_ok());
+
+    assert!(BrowserCookies::from_netscape("bing.com\tTRUE").is_err());
+}
+
+#[test]
+fn json_cookies_filtered_to_bing() {
+    let cookies = BrowserCookies::from_json(
+        r#"[
+            {"domain": ".bing.com", "name": "_U", "value": "auth", "expirationDate": 1893456000.5},
+            {"domain": "copilot.microsoft.com", "name": "_U", "value": "other"},
+            {"domain": "www.bing.com", "name": "MUID", "value": "id", "session": true}
+        ]"#,
+    )
+    .unwrap();
+    assert_eq!(cookies.to_header(), "_U=auth; MUID=id");
+    assert_eq!(cookies.get("_U"), Some("auth"));
+    assert_eq!(cookies.cookies()[0].expires, Some(1893456000));
+}
+
+#[tokio::test]
+async fn missing_auth_cookie_fails_before_create() {
+    let server = MockServer::start().await.unwrap();
+    let cookies =
+        BrowserCookies::from_json(r#"[{"domain": ".bing.com", "name": "MUID", "value": "id"}]"#)
+            .unwrap();
+
+    let result = server
+        .builder()
+        .browser_cookies(&cookies)
+        .new_conversation(Tone::Precise)
+        .await;
+    assert!(matches!(result, Err(SydneyError::MissingAuthCookie)));
+    assert_eq!(server.conversations_created(), 0);
+
+    let cookies = BrowserCookies::from_netscape(NETSCAPE_COOKIES).unwrap();
+    server
+        .builder()
+        .browser_cookies(&cookies)
+        .new_conversation(Tone::Precise)
+        .await
+        .unwrap();
+    assert_eq!(server.conversations_created(), 1);
+}
+
+#[tokio::test]
+async fn cookie_jar_persists_between_runs() {
+    let server = MockServer::start().await.unwrap();
+    let path = std::env::temp_dir().join(format!("bing-ai-cookies-{}.json", std::process::id()));
+    _ = std::fs::remove_file(&path);
+
+    let jar = Arc::new(CookieJar::open(&path).unwrap());
+    server
+        .builder()
+        .cookies("_U=auth")
+        .cookie_jar(jar)
+        .new_conversation(Tone::Precise)
+        .await
+        .unwrap();
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        let mode = std::fs::metadata(&path).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o600);
+    }
+
+    // Next run gets both the auth cookie and the one set by the create response
+    let jar = Arc::new(CookieJar::open(&path).unwrap());
+    assert_eq!(
+        jar.get(&server.create_url(), "SRCHSESS").as_deref(),
+        Some("mock-session-1")
+    );
+    server
+        .builder()
+        .cookie_jar(jar.clone())
+        .new_conversation(Tone::Precise)
+        .await
+        .unwrap();
+    assert_eq!(
+        jar.get(&server.create_url(), "SRCHSESS").as_deref(),
+        Some("mock-session-2")
+    );
+
+    // Explicit cookies replace the saved ones
+    server
+        .builder()
+        .cookies("_U=new")
+        .cookie_jar(Arc::new(CookieJar::open(&path).unwrap()))
+        .new_conversation(Tone::Precise)
+        .await
+        .unwrap();
+
+    let sent = server.create_cookies();
+    assert_eq!(sent[0].as_deref(), Some("_U=auth"));
+    for (cookies, expected) in sent[1..].iter().zip(["_U=auth", "_U=new"]) {
+        let cookies = cookies.as_deref().unwrap();
+        assert!(cookies.contains(expected), "{cookies}");
+        assert!(cookies.contains("SRCHSESS=mock-session-"), "{cookies}");
+    }
+
+    _ = std::fs::remove_file(&path);
+}
+
+#[tokio::test]
+async fn transcript_failure_keeps_answer() {
+    let server = MockServer::start().await.unwrap();
+    server.push_answer(vec![MockAction::Final("Paris".to_string())]);
+
+    // Store directory can't be created, its parent is a file
+    let file = std::env::temp_dir().join(format!("bing-ai-not-a-dir-{}", std::process::id()));
+    std::fs::write(&file, "").unwrap();
+
+    let mut ai = server.new_conversation(Tone::Precise).await.unwrap();
+    ai.set_transcript_store(Some(TranscriptStore::new(file.join("transcripts"))));
+    let answer = ai.ask(PROMPT).await.unwrap().final_text().await;
+    _ = std::fs::remove_file(&file);
+
+    assert_eq!(answer.unwrap(), "Paris");
+}